@@ -0,0 +1,127 @@
+//! Known correct answers, stored in a table per input name
+//!
+//! ```toml
+//! [default]
+//! day01.part1 = "538"
+//!
+//! [example]
+//! day07.part1 = "CABDFE"
+//! ```
+
+use crate::config::{Config, ConfigError};
+use crate::document::Document;
+use crate::error::ParseError;
+use crate::solution::Part;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, ParseError),
+    Config(ConfigError),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnswersError::Io(path, e) => write!(f, "failed to access {}: {}", path.display(), e),
+            AnswersError::Parse(path, e) => write!(f, "failed to parse {}: {}", path.display(), e),
+            AnswersError::Config(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AnswersError {}
+
+impl From<ConfigError> for AnswersError {
+    fn from(e: ConfigError) -> Self {
+        AnswersError::Config(e)
+    }
+}
+
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct Answers {
+    document: Document,
+}
+
+fn key(day: u8, part: Part) -> String {
+    format!("day{:02}.part{}", day, part)
+}
+
+impl Answers {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Answers { document: Document::parse(input)? })
+    }
+
+    /// Path of the answers file, from the `answers` config setting or `answers.toml`
+    /// in this crate
+    pub fn default_path() -> Result<PathBuf, AnswersError> {
+        let path = Config::load()?.answers
+            .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("answers.toml"));
+
+        Ok(path)
+    }
+
+    /// Loads answers from a file, treating a missing file as having no answers
+    pub fn load(path: &Path) -> Result<Self, AnswersError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents)
+                .map_err(|e| AnswersError::Parse(path.to_path_buf(), e)),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(AnswersError::Io(path.to_path_buf(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AnswersError> {
+        fs::write(path, self.to_string())
+            .map_err(|e| AnswersError::Io(path.to_path_buf(), e))
+    }
+
+    pub fn get(&self, input: &str, day: u8, part: Part) -> Option<&str> {
+        self.document.get(input, &key(day, part))
+    }
+
+    pub fn set(&mut self, input: &str, day: u8, part: Part, answer: &str) {
+        self.document.set(input, &key(day, part), answer);
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.document.to_string().trim_start())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_answers() {
+        let answers = Answers::parse("[default]\nday01.part1 = \"538\"\n\n[example]\nday07.part1 = \"CABDFE\"").unwrap();
+
+        assert_eq!(Some("538"), answers.get("default", 1, Part::One));
+        assert_eq!(Some("CABDFE"), answers.get("example", 7, Part::One));
+        assert_eq!(None, answers.get("default", 1, Part::Two));
+        assert_eq!(None, answers.get("alice", 1, Part::One));
+    }
+
+    #[test]
+    fn set_answers() {
+        let mut answers = Answers::default();
+        answers.set("alice", 4, Part::Two, "28198");
+
+        assert_eq!("[alice]\nday04.part2 = \"28198\"\n", answers.to_string());
+    }
+
+    #[test]
+    fn shipped_answers_file() {
+        let answers = Answers::load(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/answers.toml"))).unwrap();
+
+        assert_eq!(Some("77271"), answers.get("default", 1, Part::Two));
+    }
+}
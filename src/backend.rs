@@ -0,0 +1,161 @@
+//! Parser backends, so each day's input can be parsed by more than one implementation
+//!
+//! Every day implements `Parser` for both the nom parsers and a hand-written parser
+//! built on `Scanner`. `Solution::parse` uses whichever backend is selected at build
+//! time: nom by default, or the hand-written parsers with the `hand-parser` feature.
+
+use nom::IResult;
+use nom::types::CompleteStr;
+
+use crate::error::{ParseError, finish};
+use crate::scanner::{Scanner, complete};
+use crate::solution::Solution;
+
+/// Parsers written with nom macros and the combinators in `parsers`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Nom;
+
+/// Parsers written by hand on top of `scanner::Scanner`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Hand;
+
+/// The backend used by `Solution::parse`
+#[cfg(not(feature = "hand-parser"))]
+pub type Backend = Nom;
+
+/// The backend used by `Solution::parse`
+#[cfg(feature = "hand-parser")]
+pub type Backend = Hand;
+
+/// Parses a day's input using the backend `B`
+///
+/// Every backend must produce the same value, or the same error at the same
+/// position, for any input.
+pub trait Parser<B>: Solution {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError>;
+}
+
+/// Parses a single value such as one line of input with the selected backend,
+/// allowing only whitespace after it, for use by `FromStr` implementations
+pub(crate) fn parse_item<'a, T, N, H>(input: &'a str, nom: N, hand: H) -> Result<T, ParseError>
+    where N: FnOnce(CompleteStr<'a>) -> IResult<CompleteStr<'a>, T>,
+          H: FnOnce(&mut Scanner<'a>) -> Result<T, ParseError>
+{
+    if cfg!(feature = "hand-parser") {
+        complete(input, hand)
+    } else {
+        finish(input, nom(CompleteStr(input)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{Rng, generate};
+    use crate::input::Inputs;
+    use crate::{day01, day02, day03, day04, day05, day06, day07, day08, day09};
+
+    use std::fmt::Debug;
+
+    const EXAMPLES: &[(u8, &str)] = &[
+        (1, "+1\n-2\n+3\n+1"),
+        (1, "+1, +1, -2"),
+        (1, ""),
+        (1, "   \n"),
+        (1, "1"),
+        (1, "+\n"),
+        (1, "+1\n\n+2"),
+        (1, "+99999999999"),
+        (1, "+1 , -2\t3\n4"),
+        (1, "+1,"),
+        (1, "+1,,2"),
+        (1, "9223372036854775808"),
+        (2, "abcdef\nbababc\r\nabbcde\n\n"),
+        (3, "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2\n"),
+        (4, "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep\n[1518-11-01 00:25] wakes up"),
+        (4, "[1518-11-01 00:00] Guard #x begins shift"),
+        (4, "[1518-11-01 00:00] Guard #99999 begins shift"),
+        (4, "[1518-11-01 00:00] Guard #10 begins"),
+        (5, "dabAcCaCBAcCcaDA\n"),
+        (6, "1, 1\n-1, 6\n8, +3"),
+        (6, "1, 1\n2,3"),
+        (7, "Step C must be finished before step A can begin.\nStep C must be finished before step F can begin."),
+        (7, "Step "),
+        (7, "Step é must be finished before step A can begin."),
+        (8, "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2"),
+        (8, "1 1\n0 1 5\n7\n"),
+        (8, "1 1 0"),
+        (9, "10 players; last marble is worth 1618 points"),
+        (9, "10 players; last marble is worth 1618 points and more"),
+    ];
+
+    fn assert_backends_agree<S>(input: &str)
+        where S: Parser<Nom> + Parser<Hand>, S::Input: PartialEq + Debug
+    {
+        let nom = <S as Parser<Nom>>::parse_input(input);
+        let hand = <S as Parser<Hand>>::parse_input(input);
+
+        assert_eq!(nom, hand, "backends disagree on day {} input {:?}", S::DAY, input);
+    }
+
+    fn check(day: u8, input: &str) {
+        match day {
+            1 => assert_backends_agree::<day01::Day01>(input),
+            2 => assert_backends_agree::<day02::Day02>(input),
+            3 => assert_backends_agree::<day03::Day03>(input),
+            4 => assert_backends_agree::<day04::Day04>(input),
+            5 => assert_backends_agree::<day05::Day05>(input),
+            6 => assert_backends_agree::<day06::Day06>(input),
+            7 => assert_backends_agree::<day07::Day07>(input),
+            8 => assert_backends_agree::<day08::Day08>(input),
+            9 => assert_backends_agree::<day09::Day09>(input),
+            _ => panic!("no parser for day {}", day),
+        }
+    }
+
+    /// Changes the input at a random character boundary, so the backends are also
+    /// compared on malformed input
+    fn mutate(rng: &mut Rng, input: &str) -> String {
+        const NOISE: [&str; 8] = ["", "x", " ", "\n", "-", "9", "99999999999999999999", "#"];
+
+        let boundaries = input.char_indices().map(|(i, _)| i).chain(Some(input.len())).collect::<Vec<usize>>();
+        let start = *rng.choose(&boundaries);
+        let end = boundaries.iter().copied().filter(|&i| i >= start).take(1 + rng.below(3) as usize).last().unwrap();
+
+        format!("{}{}{}", &input[..start], rng.choose(&NOISE), &input[end..])
+    }
+
+    #[test]
+    fn backends_agree_on_examples() {
+        for &(day, input) in EXAMPLES {
+            check(day, input);
+        }
+    }
+
+    #[test]
+    fn backends_agree_on_real_inputs() {
+        let inputs = Inputs::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src"));
+
+        for day in 1..=9 {
+            for name in inputs.names(day).unwrap() {
+                check(day, &inputs.load(day, &name).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn backends_agree_on_generated_and_mutated_inputs() {
+        let mut rng = Rng::new(14);
+
+        for day in 1..=9 {
+            for seed in 0..4 {
+                let input = generate(day, seed, 20).unwrap();
+                check(day, &input);
+
+                for _ in 0..50 {
+                    check(day, &mutate(&mut rng, &input));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Repeatedly times the parse and solve phases of a day
+//!
+//! Each phase is run a fixed number of times and summarised by its minimum, median
+//! and maximum time, so results are comparable across commits without relying on
+//! any external benchmarking service.
+
+use crate::error::ParseError;
+use crate::json::{self, Object};
+use crate::solution::{Part, Solver};
+
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// One of the timed steps of solving a day
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Phase {
+    Parse,
+    Solve(Part),
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Solve(part) => write!(f, "part{}", part),
+        }
+    }
+}
+
+/// Summary of the times taken by repeated runs of a phase
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Summarises a non-empty set of samples, taking the mean of the two middle
+    /// samples as the median when there is an even number of them
+    pub fn from_samples(samples: &mut [Duration]) -> Self {
+        assert!(!samples.is_empty(), "no samples to summarise");
+        samples.sort();
+
+        let middle = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[middle - 1] + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+
+        Stats { min: samples[0], median, max: samples[samples.len() - 1] }
+    }
+
+    /// Bytes of input processed per second at the median time, or `None` if the
+    /// median was too short to measure
+    pub fn throughput(&self, bytes: usize) -> Option<f64> {
+        let seconds = self.median.as_secs_f64();
+        if seconds > 0.0 { Some(bytes as f64 / seconds) } else { None }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PhaseBench {
+    pub phase: Phase,
+    pub stats: Stats,
+}
+
+/// Timings for every phase of one day against one input
+#[derive(Debug, PartialEq, Clone)]
+pub struct Bench {
+    pub day: u8,
+    pub input: String,
+    pub input_bytes: usize,
+    pub iterations: usize,
+    pub phases: Vec<PhaseBench>,
+}
+
+fn time<T, F: FnMut() -> T>(iterations: usize, mut f: F) -> Stats {
+    let mut samples = (0..iterations)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect::<Vec<Duration>>();
+
+    Stats::from_samples(&mut samples)
+}
+
+/// Times parsing the input, then each part, `iterations` times each
+///
+/// Parts are solved from a single parsed input, so their times don't include parsing.
+pub fn bench(solver: &dyn Solver, input_name: &str, input: &str, iterations: usize) -> Result<Bench, ParseError> {
+    assert!(iterations > 0, "at least one iteration is needed");
+
+    let parsed = solver.parse(input)?;
+    let mut phases = vec![PhaseBench { phase: Phase::Parse, stats: time(iterations, || solver.parse(black_box(input))) }];
+
+    for &part in &Part::ALL {
+        phases.push(PhaseBench { phase: Phase::Solve(part), stats: time(iterations, || parsed.solve(part)) });
+    }
+
+    Ok(Bench {
+        day: solver.day(),
+        input: String::from(input_name),
+        input_bytes: input.len(),
+        iterations,
+        phases,
+    })
+}
+
+impl Bench {
+    /// Writes the timings as a JSON object, with durations in nanoseconds
+    pub fn to_json(&self) -> Object {
+        let phases = self.phases.iter()
+            .map(|phase| {
+                Object::new()
+                    .string("phase", &phase.phase.to_string())
+                    .number("min_ns", phase.stats.min.as_nanos())
+                    .number("median_ns", phase.stats.median.as_nanos())
+                    .number("max_ns", phase.stats.max.as_nanos())
+                    .optional_number("bytes_per_second", phase.stats.throughput(self.input_bytes).map(f64::round))
+                    .to_string()
+            });
+
+        Object::new()
+            .number("day", self.day)
+            .string("input", &self.input)
+            .number("input_bytes", self.input_bytes)
+            .number("iterations", self.iterations)
+            .raw("phases", json::array(phases))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solver;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn stats_odd_samples() {
+        let stats = Stats::from_samples(&mut [ms(5), ms(1), ms(3)]);
+
+        assert_eq!(Stats { min: ms(1), median: ms(3), max: ms(5) }, stats);
+        assert_eq!(Some(2000.0), stats.throughput(6));
+    }
+
+    #[test]
+    fn stats_even_samples() {
+        let stats = Stats::from_samples(&mut [ms(4), ms(1), ms(2), ms(8)]);
+
+        assert_eq!(ms(3), stats.median);
+        assert_eq!(None, Stats::from_samples(&mut [Duration::from_secs(0)]).throughput(10));
+    }
+
+    #[test]
+    fn bench_every_phase() {
+        let bench = bench(&*solver(1).unwrap(), "example", "+1\n-2\n+3\n+1", 3).unwrap();
+
+        assert_eq!((1, 11, 3), (bench.day, bench.input_bytes, bench.iterations));
+        assert_eq!(
+            vec![Phase::Parse, Phase::Solve(Part::One), Phase::Solve(Part::Two)],
+            bench.phases.iter().map(|phase| phase.phase).collect::<Vec<_>>()
+        );
+        assert!(bench.phases.iter().all(|phase| phase.stats.min <= phase.stats.median && phase.stats.median <= phase.stats.max));
+    }
+
+    #[test]
+    fn bench_parse_error() {
+        assert!(bench(&*solver(1).unwrap(), "example", "x", 3).is_err());
+    }
+
+    #[test]
+    fn bench_json() {
+        let bench = Bench {
+            day: 5,
+            input: String::from("default"),
+            input_bytes: 1000,
+            iterations: 2,
+            phases: vec![PhaseBench { phase: Phase::Solve(Part::Two), stats: Stats { min: ms(1), median: ms(2), max: ms(3) } }],
+        };
+
+        assert_eq!(
+            r#"{"day":5,"input":"default","input_bytes":1000,"iterations":2,"phases":[{"phase":"part2","min_ns":1000000,"median_ns":2000000,"max_ns":3000000,"bytes_per_second":500000}]}"#,
+            bench.to_json().to_string()
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Command line arguments in the form `aoc <command> [--flag value]...`
+pub struct Args {
+    pub command: String,
+    flags: HashMap<String, String>,
+}
+
+impl Args {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let command = args.next().ok_or_else(|| String::from("missing command"))?;
+
+        let mut flags = HashMap::new();
+        while let Some(flag) = args.next() {
+            let name = match flag.strip_prefix("--") {
+                Some(name) => String::from(name),
+                None => return Err(format!("unexpected argument '{}'", flag)),
+            };
+            let value = args.next().ok_or_else(|| format!("missing value for --{}", name))?;
+
+            flags.insert(name, value);
+        }
+
+        Ok(Args { command, flags })
+    }
+
+    pub fn value<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        match self.flags.get(name) {
+            Some(value) => value.parse()
+                .map(Some)
+                .map_err(|_| format!("invalid value '{}' for --{}", value, name)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn required<T: FromStr>(&self, name: &str) -> Result<T, String> {
+        self.value(name)?.ok_or_else(|| format!("missing required flag --{}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Result<Args, String> {
+        Args::parse(s.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parse_flags() {
+        let args = args("run --day 4 --part 2").unwrap();
+
+        assert_eq!("run", args.command);
+        assert_eq!(Ok(4u8), args.required("day"));
+        assert_eq!(Ok(Some(2u8)), args.value("part"));
+        assert_eq!(Ok(None::<String>), args.value("input"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(args("").is_err());
+        assert!(args("run --day").is_err());
+        assert!(args("run day").is_err());
+        assert!(args("run --day four").unwrap().required::<u8>("day").is_err());
+    }
+}
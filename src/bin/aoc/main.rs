@@ -0,0 +1,450 @@
+mod args;
+
+use aoc_2018::{Part, solver, solvers};
+use aoc_2018::input::{DEFAULT, InputError, Inputs, normalize};
+use aoc_2018::answers::Answers;
+use aoc_2018::bench::bench;
+use aoc_2018::client::Client;
+use aoc_2018::config::Config;
+use aoc_2018::fetch::{Fetched, fetch_input};
+use aoc_2018::generate::generate;
+use aoc_2018::json;
+use aoc_2018::report::{self, Format, report};
+use aoc_2018::run::{run, run_all};
+use aoc_2018::submit::{Guesses, Outcome, submit};
+use aoc_2018::verify::{Status, verify};
+use aoc_2018::watch::{RunAnswers, changed_files, diff_answers, parse_run_output, snapshot};
+
+use args::Args;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::{self, Command};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+const USAGE: &str = "usage:
+    aoc run --day N [--part 1|2] [--input PATH | --name NAME]
+    aoc run-all [--name NAME] [--threads N]
+    aoc verify [--answers PATH]
+    aoc report [--day N] [--format json|csv] [--answers PATH] [--output PATH]
+    aoc fetch --day N
+    aoc submit --day N --part 1|2 [--answer ANSWER]
+    aoc bench [--day N] [--name NAME] [--iterations N] [--output PATH]
+    aoc generate --day N [--seed S] [--scale N] [--output PATH]
+    aoc watch --day N [--name NAME] [--interval MS]
+
+run     Solves the given day, reading the puzzle input from PATH, or stdin if PATH
+        is '-'. Without --input the named input is loaded from the input dir,
+        which is set by AOC_INPUT_DIR or input_dir in aoc.toml. Both parts are
+        solved unless --part is given.
+run-all Solves both parts of every day with the named input, default input.txt,
+        running parts concurrently on N threads, default one per CPU. Answers
+        and times are printed in day order.
+verify  Solves every day for every input in the input dir, comparing against the
+        known answers in answers.toml, or the file set by answers in aoc.toml.
+report  Like verify, but writes a row for each part of each input with the answer,
+        expected answer, status and parse and solve times in nanoseconds, as
+        JSON (the default) or CSV. Written to PATH if given, otherwise stdout.
+        Only the given day is reported if --day is given.
+fetch   Downloads the input for a day into the input dir, unless it is already
+        there. Requires AOC_SESSION or session in aoc.toml, and uses base_url in
+        aoc.toml if set.
+submit  Submits an answer, or the answer for the default input if --answer is
+        not given. Correct answers are saved to the answers file, and wrong
+        answers to guesses.toml, or the file set by guesses in aoc.toml, so
+        they are not submitted again.
+bench   Times parsing and solving each part of the named input, default input.txt,
+        for the given day or every day with that input. Each phase is run N
+        times, default 10, and the min, median and max times are printed along
+        with throughput. Results are also written as JSON to PATH if given.
+generate
+        Prints a random input for the given day, or writes it to PATH. The same
+        seed, default 0, always gives the same input. Scale is roughly the number
+        of lines or items, default 1000.
+watch   Solves the named input, default input.txt, whenever a file in the day's
+        source dir or input dir changes, checking every MS milliseconds, default
+        500. Each run rebuilds with cargo, so cargo must be on the path, and
+        answers which differ from the last successful run are marked with ~.";
+
+fn main() {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => exit_with_usage(&e),
+    };
+
+    let result = match args.command.as_str() {
+        "run" => run_command(&args),
+        "run-all" => run_all_command(&args),
+        "verify" => verify_command(&args),
+        "report" => report_command(&args),
+        "fetch" => fetch_command(&args),
+        "submit" => submit_command(&args),
+        "bench" => bench_command(&args),
+        "generate" => generate_command(&args),
+        "watch" => watch_command(&args),
+        "help" | "--help" | "-h" => { println!("{}", USAGE); Ok(()) },
+        command => exit_with_usage(&format!("unknown command '{}'", command)),
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("error: {}\n\n{}", error, USAGE);
+    process::exit(1);
+}
+
+fn run_command(args: &Args) -> Result<(), String> {
+    let day: u8 = args.required("day")?;
+    let parts = match args.value::<Part>("part")? {
+        Some(part) => vec![part],
+        None => Part::ALL.to_vec(),
+    };
+    let input = read_input(args, day)?;
+
+    let solver = solver(day).ok_or_else(|| format!("day {} has not been solved", day))?;
+    let run = run(&*solver, &input, &parts).map_err(|e| e.to_string())?;
+
+    println!("day {} parsed in {:.2?}", day, run.parse_elapsed);
+    for part in run.parts {
+        let answer = part.answer.unwrap_or_else(|| String::from("no answer"));
+        println!("day {} part {}: {} ({:.2?})", day, part.part, answer, part.elapsed);
+    }
+
+    Ok(())
+}
+
+fn run_all_command(args: &Args) -> Result<(), String> {
+    let name = args.value::<String>("name")?.unwrap_or_else(|| String::from(DEFAULT));
+    let threads = match args.value::<usize>("threads")? {
+        Some(0) => return Err(String::from("--threads must be at least 1")),
+        Some(threads) => threads,
+        None => thread::available_parallelism().map_or(1, |threads| threads.get()),
+    };
+    let inputs = Inputs::from_env().map_err(|e| e.to_string())?;
+
+    let solvers = solvers();
+    let mut days = vec![];
+    for solver in &solvers {
+        match inputs.load(solver.day(), &name) {
+            Ok(input) => days.push((&**solver, input)),
+            Err(InputError::NotFound(_)) => continue,
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    let start = Instant::now();
+    let days = days.iter().map(|(solver, input)| (*solver, input.as_str())).collect::<Vec<_>>();
+    let results = run_all(&days, threads);
+    let elapsed = start.elapsed();
+
+    let rows = results.iter()
+        .map(|result| {
+            let (answer, parse, solve) = match &result.result {
+                Ok(run) => (
+                    run.parts[0].answer.clone().unwrap_or_else(|| String::from("-")),
+                    format!("{:.2?}", run.parse_elapsed),
+                    format!("{:.2?}", run.parts[0].elapsed),
+                ),
+                Err(e) => (format!("parse error at line {}, column {}", e.line, e.column), String::from("-"), String::from("-")),
+            };
+
+            vec![result.day.to_string(), result.part.to_string(), answer, parse, solve]
+        })
+        .collect::<Vec<_>>();
+    print_table(&["day", "part", "answer", "parse", "solve"], &rows);
+    let plural = if threads == 1 { "" } else { "s" };
+    println!("{} parts solved in {:.2?} on {} thread{}", results.len(), elapsed, threads, plural);
+
+    Ok(())
+}
+
+fn verify_command(args: &Args) -> Result<(), String> {
+    let answers_path = match args.value::<PathBuf>("answers")? {
+        Some(path) => path,
+        None => Answers::default_path().map_err(|e| e.to_string())?,
+    };
+    let answers = Answers::load(&answers_path).map_err(|e| e.to_string())?;
+    let inputs = Inputs::from_env().map_err(|e| e.to_string())?;
+
+    let checks = verify(&inputs, &answers).map_err(|e| e.to_string())?;
+
+    let rows = checks.iter()
+        .map(|check| {
+            let answer = match &check.answer {
+                Ok(Some(answer)) => answer.clone(),
+                Ok(None) => String::from("-"),
+                Err(e) => format!("parse error at line {}, column {}", e.line, e.column),
+            };
+
+            vec![
+                check.day.to_string(),
+                check.part.to_string(),
+                check.input.clone(),
+                answer,
+                check.expected.clone().unwrap_or_else(|| String::from("-")),
+                check.status().to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_table(&["day", "part", "input", "answer", "expected", "status"], &rows);
+
+    let failures = checks.iter().filter(|check| check.status() == Status::Fail).count();
+    if failures > 0 {
+        return Err(format!("{} of {} checks failed", failures, checks.len()));
+    }
+
+    Ok(())
+}
+
+fn report_command(args: &Args) -> Result<(), String> {
+    let format = args.value::<Format>("format")?.unwrap_or(Format::Json);
+    let solvers = match args.value::<u8>("day")? {
+        Some(day) => vec![solver(day).ok_or_else(|| format!("day {} has not been solved", day))?],
+        None => solvers(),
+    };
+    let answers_path = match args.value::<PathBuf>("answers")? {
+        Some(path) => path,
+        None => Answers::default_path().map_err(|e| e.to_string())?,
+    };
+    let answers = Answers::load(&answers_path).map_err(|e| e.to_string())?;
+    let inputs = Inputs::from_env().map_err(|e| e.to_string())?;
+
+    let rows = report(&solvers, &inputs, &answers).map_err(|e| e.to_string())?;
+    let report = report::write(format, &rows);
+
+    match args.value::<PathBuf>("output")? {
+        Some(path) => fs::write(&path, report)
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e)),
+        None => { print!("{}", report); Ok(()) },
+    }
+}
+
+fn fetch_command(args: &Args) -> Result<(), String> {
+    let day: u8 = args.required("day")?;
+    let config = Config::load().map_err(|e| e.to_string())?;
+    let client = Client::from_config(&config).map_err(|e| e.to_string())?;
+    let inputs = Inputs::from_env().map_err(|e| e.to_string())?;
+
+    match fetch_input(&client, &inputs, day).map_err(|e| e.to_string())? {
+        Fetched::Cached(path) => println!("day {} input already at {}", day, path.display()),
+        Fetched::Downloaded(path) => println!("day {} input saved to {}", day, path.display()),
+    }
+
+    Ok(())
+}
+
+fn submit_command(args: &Args) -> Result<(), String> {
+    let day: u8 = args.required("day")?;
+    let part: Part = args.required("part")?;
+    let answer = match args.value::<String>("answer")? {
+        Some(answer) => answer,
+        None => {
+            let solver = solver(day).ok_or_else(|| format!("day {} has not been solved", day))?;
+            let input = Inputs::from_env()
+                .and_then(|inputs| inputs.load(day, DEFAULT))
+                .map_err(|e| e.to_string())?;
+            run(&*solver, &input, &[part]).map_err(|e| e.to_string())?
+                .parts.remove(0).answer
+                .ok_or_else(|| format!("day {} part {} has no answer", day, part))?
+        },
+    };
+
+    let config = Config::load().map_err(|e| e.to_string())?;
+    let client = Client::from_config(&config).map_err(|e| e.to_string())?;
+    let answers_path = Answers::default_path().map_err(|e| e.to_string())?;
+    let mut answers = Answers::load(&answers_path).map_err(|e| e.to_string())?;
+    let guesses_path = Guesses::default_path().map_err(|e| e.to_string())?;
+    let mut guesses = Guesses::load(&guesses_path).map_err(|e| e.to_string())?;
+
+    let outcome = submit(&client, &mut answers, &mut guesses, day, part, &answer, SystemTime::now())
+        .map_err(|e| e.to_string())?;
+    if outcome == Outcome::Correct {
+        answers.save(&answers_path).map_err(|e| e.to_string())?;
+    }
+    guesses.save(&guesses_path).map_err(|e| e.to_string())?;
+
+    println!("day {} part {}: {} is {}", day, part, answer, outcome);
+
+    Ok(())
+}
+
+fn bench_command(args: &Args) -> Result<(), String> {
+    let name = args.value::<String>("name")?.unwrap_or_else(|| String::from(DEFAULT));
+    let iterations = args.value::<usize>("iterations")?.unwrap_or(10);
+    if iterations == 0 {
+        return Err(String::from("--iterations must be at least 1"));
+    }
+
+    let solvers = match args.value::<u8>("day")? {
+        Some(day) => vec![solver(day).ok_or_else(|| format!("day {} has not been solved", day))?],
+        None => solvers(),
+    };
+    let inputs = Inputs::from_env().map_err(|e| e.to_string())?;
+
+    let mut benches = vec![];
+    for solver in solvers {
+        let input = match inputs.load(solver.day(), &name) {
+            Ok(input) => input,
+            // when benchmarking every day, skip days without the input
+            Err(InputError::NotFound(_)) if args.value::<u8>("day")?.is_none() => continue,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let bench = bench(&*solver, &name, &input, iterations)
+            .map_err(|e| format!("day {}: {}", solver.day(), e))?;
+        benches.push(bench);
+    }
+
+    let rows = benches.iter()
+        .flat_map(|bench| bench.phases.iter().map(move |phase| {
+            let throughput = phase.stats.throughput(bench.input_bytes)
+                .map_or_else(|| String::from("-"), |bytes| format!("{:.2} MB/s", bytes / 1e6));
+
+            vec![
+                bench.day.to_string(),
+                phase.phase.to_string(),
+                format!("{:.2?}", phase.stats.min),
+                format!("{:.2?}", phase.stats.median),
+                format!("{:.2?}", phase.stats.max),
+                throughput,
+            ]
+        }))
+        .collect::<Vec<_>>();
+    print_table(&["day", "phase", "min", "median", "max", "throughput"], &rows);
+
+    if let Some(path) = args.value::<PathBuf>("output")? {
+        let report = json::array(benches.iter().map(|bench| bench.to_json().to_string()));
+        fs::write(&path, report + "\n")
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    }
+
+    Ok(())
+}
+
+fn generate_command(args: &Args) -> Result<(), String> {
+    let day: u8 = args.required("day")?;
+    let seed = args.value::<u64>("seed")?.unwrap_or(0);
+    let scale = args.value::<usize>("scale")?.unwrap_or(1000);
+
+    let input = generate(day, seed, scale).ok_or_else(|| format!("no generator for day {}", day))?;
+
+    match args.value::<PathBuf>("output")? {
+        Some(path) => fs::write(&path, input + "\n")
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e)),
+        None => { println!("{}", input); Ok(()) },
+    }
+}
+
+fn watch_command(args: &Args) -> Result<(), String> {
+    let day: u8 = args.required("day")?;
+    let name = args.value::<String>("name")?.unwrap_or_else(|| String::from(DEFAULT));
+    let interval = Duration::from_millis(args.value("interval")?.unwrap_or(500));
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let inputs = Inputs::from_env().map_err(|e| e.to_string())?;
+    let mut dirs = vec![manifest_dir.join("src").join(format!("day{:02}", day)), inputs.day_dir(day)];
+    dirs.dedup();
+
+    let solve = || -> Result<RunAnswers, String> {
+        let output = Command::new("cargo")
+            .args(["run", "--quiet", "--manifest-path"])
+            .arg(manifest_dir.join("Cargo.toml"))
+            .args(["--", "run", "--day", &day.to_string(), "--name", &name])
+            .output()
+            .map_err(|e| format!("failed to run cargo: {}", e))?;
+
+        if output.status.success() {
+            Ok(parse_run_output(&String::from_utf8_lossy(&output.stdout)))
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim_end().to_string())
+        }
+    };
+
+    for dir in &dirs {
+        println!("watching {}", dir.display());
+    }
+
+    let mut files = snapshot(&dirs).map_err(|e| e.to_string())?;
+    let mut answers = RunAnswers::new();
+    let mut changed: Vec<PathBuf> = vec![];
+
+    loop {
+        for path in &changed {
+            println!("changed {}", path.display());
+        }
+
+        match solve() {
+            Ok(latest) => {
+                for diff in diff_answers(&answers, &latest) {
+                    println!("{}", diff);
+                }
+                answers = latest;
+            },
+            Err(e) => eprintln!("{}", e),
+        }
+
+        loop {
+            thread::sleep(interval);
+
+            let latest = snapshot(&dirs).map_err(|e| e.to_string())?;
+            changed = changed_files(&files, &latest);
+            files = latest;
+
+            if !changed.is_empty() {
+                break;
+            }
+        }
+    }
+}
+
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let widths = header.iter()
+        .enumerate()
+        .map(|(column, title)| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain(Some(title.len()))
+                .max()
+                .unwrap()
+        })
+        .collect::<Vec<usize>>();
+
+    let print_row = |cells: Vec<&str>| {
+        let line = cells.iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    };
+
+    print_row(header.to_vec());
+    for row in rows {
+        print_row(row.iter().map(String::as_str).collect());
+    }
+}
+
+fn read_input(args: &Args, day: u8) -> Result<String, String> {
+    match args.value::<String>("input")?.as_deref() {
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)
+                .map_err(|e| format!("failed to read stdin: {}", e))?;
+            Ok(normalize(&input))
+        },
+        Some(path) => fs::read_to_string(path)
+            .map(|input| normalize(&input))
+            .map_err(|e| format!("failed to read {}: {}", path, e)),
+        None => {
+            let name = args.value::<String>("name")?.unwrap_or_else(|| String::from(DEFAULT));
+            Inputs::from_env()
+                .and_then(|inputs| inputs.load(day, &name))
+                .map_err(|e| e.to_string())
+        },
+    }
+}
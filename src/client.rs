@@ -0,0 +1,148 @@
+//! HTTP client for an Advent of Code compatible server
+
+use crate::config::Config;
+use crate::solution::Part;
+
+use std::env;
+use std::fmt;
+use std::io::Read;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const YEAR: u16 = 2018;
+
+const USER_AGENT: &str = "github.com/JoshMcguigan/aoc-2018";
+
+#[derive(Debug)]
+pub enum ClientError {
+    MissingSession,
+    /// The server responded with a status other than success
+    Status(u16, String),
+    Transport(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientError::MissingSession => {
+                write!(f, "no session cookie, set AOC_SESSION or session in aoc.toml")
+            },
+            ClientError::Status(status, body) => write!(f, "server responded {}: {}", status, body.trim()),
+            ClientError::Transport(e) => write!(f, "request failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<ureq::Error> for ClientError {
+    fn from(e: ureq::Error) -> Self {
+        match e {
+            ureq::Error::Status(status, response) => {
+                ClientError::Status(status, response.into_string().unwrap_or_default())
+            },
+            ureq::Error::Transport(transport) => ClientError::Transport(transport.to_string()),
+        }
+    }
+}
+
+pub struct Client {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str) -> Self {
+        Client {
+            base_url: String::from(base_url.trim_end_matches('/')),
+            session: String::from(session),
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+        }
+    }
+
+    /// Uses `AOC_SESSION` or the configured session, and the configured base URL
+    pub fn from_config(config: &Config) -> Result<Self, ClientError> {
+        let session = env::var("AOC_SESSION").ok()
+            .or_else(|| config.session.clone())
+            .ok_or(ClientError::MissingSession)?;
+        let base_url = config.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+
+        Ok(Client::new(base_url, &session))
+    }
+
+    fn url(&self, day: u8, path: &str) -> String {
+        format!("{}/{}/day/{}{}", self.base_url, YEAR, day, path)
+    }
+
+    fn cookie(&self) -> String {
+        format!("session={}", self.session)
+    }
+
+    pub fn input(&self, day: u8) -> Result<String, ClientError> {
+        let response = self.agent.get(&self.url(day, "/input"))
+            .set("Cookie", &self.cookie())
+            .call()?;
+
+        read_body(response)
+    }
+
+    /// Posts an answer, returning the HTML page sent in response
+    pub fn answer(&self, day: u8, part: Part, answer: &str) -> Result<String, ClientError> {
+        let response = self.agent.post(&self.url(day, "/answer"))
+            .set("Cookie", &self.cookie())
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])?;
+
+        read_body(response)
+    }
+}
+
+fn read_body(response: ureq::Response) -> Result<String, ClientError> {
+    let mut body = String::new();
+    response.into_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| ClientError::Transport(e.to_string()))?;
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stub_server::StubServer;
+
+    #[test]
+    fn request_input() {
+        let server = StubServer::start(vec![(200, String::from("+1\n-2\n"))]);
+        let client = Client::new(&format!("{}/", server.url()), "abc123");
+
+        assert_eq!("+1\n-2\n", client.input(1).unwrap());
+
+        let requests = server.requests();
+        assert!(requests[0].starts_with("GET /2018/day/1/input HTTP/1.1\r\n"));
+        assert!(requests[0].contains("\r\ncookie: session=abc123\r\n"));
+    }
+
+    #[test]
+    fn post_answer() {
+        let server = StubServer::start(vec![(200, String::from("<article><p>That's the right answer!</p></article>"))]);
+        let client = Client::new(&server.url(), "abc123");
+
+        assert!(client.answer(7, Part::Two, "1 2").unwrap().contains("right answer"));
+
+        let requests = server.requests();
+        assert!(requests[0].starts_with("POST /2018/day/7/answer HTTP/1.1\r\n"));
+        assert!(requests[0].contains("\r\ncookie: session=abc123\r\n"));
+        assert!(requests[0].ends_with("\r\n\r\nlevel=2&answer=1+2"));
+    }
+
+    #[test]
+    fn error_status() {
+        let server = StubServer::start(vec![(404, String::from("Please don't repeatedly request this endpoint before it unlocks!"))]);
+        let client = Client::new(&server.url(), "abc123");
+
+        match client.input(25) {
+            Err(ClientError::Status(404, body)) => assert!(body.starts_with("Please don't")),
+            other => panic!("expected status error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::document::Document;
+use crate::error::ParseError;
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the config file looked for in the current directory
+pub const CONFIG_FILE: &str = "aoc.toml";
+
+/// Settings read from the `aoc.toml` config file
+///
+/// The file is optional, and every setting in it has a default. Relative paths are
+/// resolved against the directory containing the config file.
+#[derive(Debug, PartialEq, Default)]
+pub struct Config {
+    pub input_dir: Option<PathBuf>,
+    pub answers: Option<PathBuf>,
+    pub guesses: Option<PathBuf>,
+    /// Session cookie used to authenticate with Advent of Code
+    pub session: Option<String>,
+    pub base_url: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, ParseError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "failed to parse {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Loads the file named by `AOC_CONFIG`, or `aoc.toml` in the current directory
+    ///
+    /// A missing `aoc.toml` gives the default config, but a missing file named by
+    /// `AOC_CONFIG` is an error.
+    pub fn load() -> Result<Self, ConfigError> {
+        match env::var_os("AOC_CONFIG") {
+            Some(path) => Self::from_file(Path::new(&path)),
+            None if Path::new(CONFIG_FILE).exists() => Self::from_file(Path::new(CONFIG_FILE)),
+            None => Ok(Config::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        let document = Document::parse(&contents)
+            .map_err(|e| ConfigError::Parse(path.to_path_buf(), e))?;
+
+        Ok(Self::from_document(&document, path.parent().unwrap_or_else(|| Path::new(""))))
+    }
+
+    pub fn from_document(document: &Document, base_dir: &Path) -> Self {
+        let path = |key| document.get("", key).map(|value| base_dir.join(value));
+
+        let string = |key| document.get("", key).map(String::from);
+
+        Config {
+            input_dir: path("input_dir"),
+            answers: path("answers"),
+            guesses: path("guesses"),
+            session: string("session"),
+            base_url: string("base_url"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_paths() {
+        let document = Document::parse("input_dir = \"inputs\"\nanswers = \"inputs/answers.toml\"").unwrap();
+        let config = Config::from_document(&document, Path::new("/home/aoc"));
+
+        assert_eq!(Some(PathBuf::from("/home/aoc/inputs")), config.input_dir);
+        assert_eq!(Some(PathBuf::from("/home/aoc/inputs/answers.toml")), config.answers);
+    }
+
+    #[test]
+    fn strings() {
+        let document = Document::parse("session = \"53616c74\"\nbase_url = \"http://localhost:8080\"").unwrap();
+        let config = Config::from_document(&document, Path::new("/home/aoc"));
+
+        assert_eq!(Some(String::from("53616c74")), config.session);
+        assert_eq!(Some(String::from("http://localhost:8080")), config.base_url);
+    }
+
+    #[test]
+    fn absolute_paths() {
+        let document = Document::parse("input_dir = \"/var/aoc\"").unwrap();
+        let config = Config::from_document(&document, Path::new("/home/aoc"));
+
+        assert_eq!(Some(PathBuf::from("/var/aoc")), config.input_dir);
+    }
+
+    #[test]
+    fn defaults() {
+        let config = Config::from_document(&Document::default(), Path::new("/home/aoc"));
+
+        assert_eq!(Config::default(), config);
+    }
+}
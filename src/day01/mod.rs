@@ -1,66 +1,593 @@
 use nom::{
     named,
-    alt,
-    tag,
-    error_position,
-    do_parse,
+    map,
     call,
-    eol,
-    separated_list,
-    map_res,
-    recognize,
 };
 use nom::types::CompleteStr;
-use std::str::FromStr;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str::{self, FromStr};
+
+use crate::backend::{Backend, Hand, Nom, Parser, parse_item};
+use crate::error::{ErrorKind, ParseError, finish};
+use crate::parsers::{list, signed};
+use crate::scanner::{Scanner, complete};
+use crate::solution::Solution;
 
 #[derive(Debug, PartialEq)]
-enum Sign {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sign {
     Pos,
     Neg,
 }
 
-named!(
-    sign<CompleteStr, Sign>,
-    alt!(
-        tag!("+") => { |_| Sign::Pos } |
-        tag!("-") => { |_| Sign::Neg }
-    )
-);
+impl fmt::Display for Sign {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Sign::Pos => write!(f, "+"),
+            Sign::Neg => write!(f, "-"),
+        }
+    }
+}
 
+/// A change in frequency
+///
+/// Parsed values always fit in an `i64`, so `value` is at most `2^63` for a
+/// negative change and `2^63 - 1` for a positive one.
 #[derive(Debug, PartialEq)]
-struct Change {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Change {
     pub sign: Sign,
-    pub value: u32,
+    pub value: u64,
+}
+
+impl Change {
+    pub fn delta(&self) -> i64 {
+        match self.sign {
+            Sign::Pos => self.value as i64,
+            Sign::Neg => (self.value as i64).wrapping_neg(),
+        }
+    }
+}
+
+/// Writes the change with an explicit sign, as in the puzzle input
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.sign, self.value)
+    }
+}
+
+impl From<i64> for Change {
+    fn from(delta: i64) -> Self {
+        let sign = if delta < 0 { Sign::Neg } else { Sign::Pos };
+
+        Change { sign, value: delta.unsigned_abs() }
+    }
 }
 
+// A change with an optional sign, where no sign means the change is positive
 named!(
-    change<CompleteStr, Change>,
-    do_parse!(
-        sign: sign >>
-        value: map_res!(recognize!(nom::digit), |CompleteStr(s)| u32::from_str(s)) >>
-        (Change { sign, value })
-    )
+    pub change<CompleteStr, Change>,
+    map!(call!(signed::<i64>), Change::from)
 );
 
+// Changes separated by commas, whitespace, line endings or any mix of them
 named!(
-    changes<CompleteStr, Vec<Change> >,
-    separated_list!(eol, change)
+    pub changes<CompleteStr, Vec<Change> >,
+    call!(list, change)
 );
 
+fn scan_change(scanner: &mut Scanner) -> Result<Change, ParseError> {
+    scanner.signed::<i64>().map(Change::from)
+}
+
+impl FromStr for Change {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_item(s, change, scan_change)
+    }
+}
+
+/// The frequency after applying every change once, or `None` if it overflows
+pub fn resulting_frequency(changes: &[Change]) -> Option<i64> {
+    changes.iter()
+        .try_fold(0i64, |acc, change| acc.checked_add(change.delta()))
+}
+
+/// Frequencies reached before each change in the first pass over the changes, or
+/// `None` if any of them overflows
+fn prefix_sums(changes: &[Change]) -> Option<Vec<i64>> {
+    let mut acc = 0i64;
+
+    changes.iter()
+        .map(|change| {
+            let frequency = acc;
+            acc = acc.checked_add(change.delta())?;
+            Some(frequency)
+        })
+        .collect()
+}
+
+/// A frequency from the first cycle which is reached again later
+///
+/// `cycle` counts passes over the changes from 0, and `index` is the position of
+/// the change about to be applied when the frequency is reached again.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Repeat {
+    pub frequency: i64,
+    pub cycle: usize,
+    pub index: usize,
+}
+
+/// How the frequency moves when the changes are applied over and over
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriftAnalysis {
+    /// Change in frequency over one pass of the changes
+    pub drift: i64,
+    /// Lowest frequency reached in the first cycle, including the starting 0
+    pub min: i64,
+    /// Highest frequency reached in the first cycle, including the starting 0
+    pub max: i64,
+    /// Each frequency from the first cycle which is ever reached again, at the first
+    /// time it is, in the order they are reached
+    pub repeats: Vec<Repeat>,
+    /// Number of cycles started before the first repeat, or `None` if nothing repeats
+    pub cycles: Option<usize>,
+}
+
+/// Finds when each frequency from the first cycle is next reached
+///
+/// The frequency before change `i` in cycle `k` is `sums[i] + k * drift`, so a
+/// frequency can only be revisited in a later cycle by one which is congruent to it
+/// modulo the drift and on the side the drift moves away from. Within each residue
+/// the nearest such frequency gets there first, so sorting each residue by
+/// frequency finds every repeat in O(n log n).
+fn repeats(sums: &[i64], drift: i64) -> Vec<Repeat> {
+    // every index at which each frequency is reached, by residue, widened as the
+    // remainder of i64::MIN by -1 overflows an i64
+    let mut residues = HashMap::new();
+    for (index, &frequency) in sums.iter().enumerate() {
+        let frequency_wide = i128::from(frequency);
+        let key = if drift == 0 { frequency_wide } else { frequency_wide.rem_euclid(i128::from(drift)) };
+        residues.entry(key).or_insert_with(Vec::new).push((frequency, index));
+    }
+
+    let mut repeats = vec![];
+
+    for group in residues.values_mut() {
+        group.sort_unstable();
+
+        // each distinct frequency with the first two indices it is reached at
+        let mut distinct: Vec<(i64, usize, Option<usize>)> = vec![];
+        for &(frequency, index) in group.iter() {
+            match distinct.last_mut() {
+                Some(last) if last.0 == frequency => { last.2.get_or_insert(index); },
+                _ => distinct.push((frequency, index, None)),
+            }
+        }
+
+        for (position, &(frequency, first, second)) in distinct.iter().enumerate() {
+            let repeat = match second {
+                Some(second) => Some((0, second)),
+                None if drift == 0 => Some((1, first)),
+                None => {
+                    // the nearest frequency which the drift carries onto this one
+                    let source = if drift > 0 {
+                        position.checked_sub(1).map(|previous| distinct[previous])
+                    } else {
+                        distinct.get(position + 1).copied()
+                    };
+
+                    source.map(|(source, index, _)| {
+                        // widened, as frequencies far apart can differ by more than an i64 holds
+                        let cycles = (frequency as i128 - source as i128) / drift as i128;
+                        (cycles as usize, index)
+                    })
+                },
+            };
+
+            if let Some((cycle, index)) = repeat {
+                repeats.push(Repeat { frequency, cycle, index });
+            }
+        }
+    }
+
+    repeats.sort_unstable_by_key(|repeat| (repeat.cycle, repeat.index));
+    repeats
+}
+
+/// Finds the first frequency reached twice when the changes are applied over and
+/// over, returning `None` if no frequency ever repeats or the first cycle overflows
+pub fn first_repeated_frequency(changes: &[Change]) -> Option<i64> {
+    if changes.is_empty() { return None }
+
+    repeats(&prefix_sums(changes)?, resulting_frequency(changes)?).first()
+        .map(|repeat| repeat.frequency)
+}
+
+/// Describes the drift of the frequency and every repeat in it, for calibration
+/// tools which need more than the first repeat
+///
+/// Returns `None` if a frequency in the first cycle overflows.
+pub fn drift_analysis(changes: &[Change]) -> Option<DriftAnalysis> {
+    let sums = prefix_sums(changes)?;
+    let drift = resulting_frequency(changes)?;
+    let reached = sums.iter().copied().chain(Some(drift));
+
+    let repeats = if changes.is_empty() { vec![] } else { repeats(&sums, drift) };
+
+    Some(DriftAnalysis {
+        drift,
+        min: reached.clone().min().unwrap_or(0),
+        max: reached.max().unwrap_or(0),
+        cycles: repeats.first().map(|repeat| repeat.cycle + 1),
+        repeats,
+    })
+}
+
+/// Returned when changes can't be read from a stream
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "failed to read changes: {}", e),
+            StreamError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+impl From<ParseError> for StreamError {
+    fn from(e: ParseError) -> Self {
+        StreamError::Parse(e)
+    }
+}
+
+/// How much of the current line is kept to show in errors
+const LINE_CONTEXT: usize = 4096;
+
+/// Longer than any change, so scanning this much of a token finds the same error as
+/// scanning all of it
+const MAX_TOKEN: usize = 32;
+
+fn is_separator(c: char) -> bool {
+    c == ',' || c.is_whitespace()
+}
+
+/// The longest prefix of `buffer` which is whole UTF-8, allowing a character cut off
+/// by the end of a read unless the reader is exhausted
+fn valid_text(buffer: &[u8], eof: bool) -> io::Result<&str> {
+    match str::from_utf8(buffer) {
+        Ok(text) => Ok(text),
+        Err(e) if e.error_len().is_none() && !eof => {
+            Ok(str::from_utf8(&buffer[..e.valid_up_to()]).unwrap())
+        },
+        Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+    }
+}
+
+/// How much of `text` can be scanned without cutting a change or a separator short,
+/// or `None` if more must be read first
+fn scan_end(text: &str, eof: bool) -> Option<usize> {
+    if eof {
+        return Some(text.len());
+    }
+
+    let trimmed = text.trim_end_matches(is_separator);
+    if trimmed.len() < text.len() {
+        return Some(trimmed.len()).filter(|&end| end > 0);
+    }
+
+    // the last token may go on in the next read, unless it is already too long
+    let token_start = text.rfind(is_separator).map_or(0, |i| i + text[i..].chars().next().unwrap().len_utf8());
+    if text.len() - token_start > MAX_TOKEN {
+        return Some(text.len());
+    }
+    Some(text[..token_start].trim_end_matches(is_separator).len()).filter(|&end| end > 0)
+}
+
+/// The outcome of scanning what has been read so far
+enum Scan {
+    More,
+    End,
+    Change(usize, Change),
+    Error(usize, ParseError),
+}
+
+/// Parses changes from a reader one at a time, with the same grammar and errors as
+/// `changes`
+///
+/// Input is read in chunks of the reader's buffer size and dropped once parsed, so
+/// only a few changes are held in memory however the input is split into lines. Errors
+/// show the whole line unless it is longer than 4096 bytes, in which case the line text
+/// starts partway through it.
+pub struct ChangeReader<R> {
+    reader: R,
+    /// Input read but not parsed yet, starting at a change or the separator before one
+    buffer: Vec<u8>,
+    /// The parsed part of the current line, or `None` if it was too long to keep
+    line_start: Option<String>,
+    line: usize,
+    column: usize,
+    started: bool,
+    eof: bool,
+    done: bool,
+}
+
+impl<R: BufRead> ChangeReader<R> {
+    pub fn new(reader: R) -> Self {
+        ChangeReader {
+            reader,
+            buffer: vec![],
+            line_start: Some(String::new()),
+            line: 1,
+            column: 1,
+            started: false,
+            eof: false,
+            done: false,
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let chunk = self.reader.fill_buf()?;
+        let len = chunk.len();
+        self.buffer.extend_from_slice(chunk);
+        self.reader.consume(len);
+        self.eof = len == 0;
+        Ok(())
+    }
+
+    /// Scans the next change from the buffer, using the scanner's list separators
+    fn scan_next(&self) -> io::Result<Scan> {
+        let text = valid_text(&self.buffer, self.eof)?;
+        let end = match scan_end(text, self.eof) {
+            Some(end) => end,
+            None => return Ok(Scan::More),
+        };
+
+        let mut scanner = Scanner::new(&text[..end]);
+        let has_change = if self.started {
+            if !scanner.list_separator() {
+                scanner.skip_whitespace();
+                if !scanner.is_empty() {
+                    return Ok(Scan::Error(end, scanner.error(ErrorKind::TrailingInput)));
+                }
+                false
+            } else {
+                true
+            }
+        } else {
+            !scanner.is_empty()
+        };
+
+        if !has_change {
+            return Ok(if self.eof { Scan::End } else { Scan::More });
+        }
+        Ok(match scan_change(&mut scanner) {
+            Ok(change) => Scan::Change(scanner.offset(), change),
+            Err(e) => Scan::Error(end, e),
+        })
+    }
+
+    /// Drops the parsed input from the buffer, keeping track of where it ended
+    fn advance(&mut self, consumed: usize) {
+        let text = str::from_utf8(&self.buffer[..consumed]).unwrap();
+        let rest = match text.rfind('\n') {
+            Some(i) => {
+                self.line += text.matches('\n').count();
+                self.column = 1;
+                self.line_start = Some(String::new());
+                &text[i + 1..]
+            },
+            None => text,
+        };
+
+        self.column += rest.chars().count();
+        if let Some(line_start) = &mut self.line_start {
+            if line_start.len() + rest.len() > LINE_CONTEXT {
+                self.line_start = None;
+            } else {
+                line_start.push_str(rest);
+            }
+        }
+        self.buffer.drain(..consumed);
+    }
+
+    /// Moves an error from the buffer to where it is in the whole input, reading the
+    /// rest of its line to show
+    fn locate(&mut self, end: usize, mut error: ParseError) -> Result<ParseError, StreamError> {
+        while !self.eof && !self.buffer[end..].contains(&b'\n') && self.buffer.len() < end + LINE_CONTEXT {
+            self.fill()?;
+        }
+
+        let text = String::from_utf8_lossy(&self.buffer);
+        let line_text = text.split('\n').nth(error.line - 1).unwrap_or("").trim_end_matches('\r');
+        if error.line == 1 {
+            error.column += self.column - 1;
+            error.line_text = format!("{}{}", self.line_start.as_ref().map_or("", String::as_str), line_text);
+        } else {
+            error.line_text = String::from(line_text);
+        }
+        error.line += self.line - 1;
+
+        Ok(error)
+    }
+
+    fn read_change(&mut self) -> Result<Option<Change>, StreamError> {
+        loop {
+            match self.scan_next()? {
+                Scan::More => self.fill()?,
+                Scan::End => return Ok(None),
+                Scan::Change(consumed, change) => {
+                    self.advance(consumed);
+                    self.started = true;
+                    return Ok(Some(change));
+                },
+                Scan::Error(end, e) => return Err(self.locate(end, e)?.into()),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ChangeReader<R> {
+    type Item = Result<Change, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.read_change();
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+        result.transpose()
+    }
+}
+
+/// Applies every change from the reader once, holding only a few changes in
+/// memory, and returns `Ok(None)` if the frequency overflows
+pub fn read_resulting_frequency<R: BufRead>(reader: R) -> Result<Option<i64>, StreamError> {
+    ChangeReader::new(reader)
+        .try_fold(Some(0i64), |acc, change| {
+            let delta = change?.delta();
+            Ok(acc.and_then(|acc| acc.checked_add(delta)))
+        })
+}
+
+/// Finds the first repeated frequency of the changes from the reader, returning
+/// `Ok(None)` if no frequency ever repeats or the first cycle overflows
+///
+/// Repeats may be in later cycles, so the frequency before each change is kept, but
+/// neither the input nor the parsed changes are.
+pub fn read_first_repeated_frequency<R: BufRead>(reader: R) -> Result<Option<i64>, StreamError> {
+    let mut sums = vec![];
+    let mut acc = Some(0i64);
+
+    for change in ChangeReader::new(reader) {
+        let delta = change?.delta();
+        if let Some(frequency) = acc {
+            sums.push(frequency);
+        }
+        acc = acc.and_then(|acc| acc.checked_add(delta));
+    }
+
+    Ok(match acc {
+        Some(drift) if !sums.is_empty() => repeats(&sums, drift).first().map(|repeat| repeat.frequency),
+        _ => None,
+    })
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+
+    type Input = Vec<Change>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        <Self as Parser<Backend>>::parse_input(input)
+    }
+
+    fn part1(changes: &Self::Input) -> Option<Self::Part1> {
+        resulting_frequency(changes)
+    }
+
+    fn part2(changes: &Self::Input) -> Option<Self::Part2> {
+        first_repeated_frequency(changes)
+    }
+}
+
+impl Parser<Nom> for Day01 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        finish(input, changes(CompleteStr(input)))
+    }
+}
+
+impl Parser<Hand> for Day01 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        complete(input, |scanner| scanner.list(scan_change))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::error::ErrorKind;
+    use crate::generate::{self, Rng};
     use std::collections::HashSet;
+    use std::io::BufReader;
 
-    #[test]
-    fn sign_positive() {
-        assert_eq!(Sign::Pos, sign(CompleteStr("+")).unwrap().1);
+    /// Applies the changes cycle by cycle, giving up after `max_cycles`
+    fn first_repeated_frequency_brute_force(changes: &[Change], max_cycles: usize) -> Option<i64> {
+        let mut frequencies = HashSet::new();
+        let mut acc = 0;
+
+        for _ in 0..max_cycles {
+            for change in changes {
+                if !frequencies.insert(acc) {
+                    return Some(acc);
+                }
+
+                acc += change.delta();
+            }
+        }
+
+        None
     }
 
-    #[test]
-    fn sign_negative() {
-        assert_eq!(Sign::Neg, sign(CompleteStr("-")).unwrap().1);
+    /// Applies the changes cycle by cycle, recording when each frequency from the
+    /// first cycle is next reached
+    fn repeats_brute_force(changes: &[Change], max_cycles: usize) -> Vec<Repeat> {
+        let sums = prefix_sums(changes).unwrap();
+        let drift = resulting_frequency(changes).unwrap();
+        let mut first_cycle = sums.iter().copied().collect::<HashSet<i64>>();
+        let mut repeats = vec![];
+
+        for cycle in 0..max_cycles {
+            for (index, sum) in sums.iter().enumerate() {
+                let frequency = sum + cycle as i64 * drift;
+                let first_visit = cycle == 0 && sums[..index].iter().all(|&earlier| earlier != frequency);
+
+                if !first_visit && first_cycle.remove(&frequency) {
+                    repeats.push(Repeat { frequency, cycle, index });
+                }
+            }
+        }
+
+        repeats
+    }
+
+    /// Reads changes through a tiny buffer, so lines span several reads
+    fn read_changes(input: &str) -> Result<Vec<Change>, ParseError> {
+        ChangeReader::new(BufReader::with_capacity(4, input.as_bytes()))
+            .collect::<Result<Vec<Change>, StreamError>>()
+            .map_err(|e| match e {
+                StreamError::Parse(e) => e,
+                StreamError::Io(e) => panic!("{}", e),
+            })
+    }
+
+    fn serialize(changes: &[Change]) -> String {
+        changes.iter().map(Change::to_string).collect::<Vec<String>>().join("\n")
     }
 
     #[test]
@@ -109,51 +636,236 @@ mod tests {
     }
 
     #[test]
-    fn part1(){
-        let input = fs::read_to_string("./src/day01/input.txt").unwrap();
+    fn parse_serialize_round_trip() {
+        generate::assert_round_trip::<Day01>(0..20, generate::day01, |_| 50, |changes| serialize(changes));
+    }
 
-        let changes : Vec<Change> = changes(CompleteStr(&input)).unwrap().1;
+    #[test]
+    fn serialize_canonical() {
+        assert_eq!("+1\n+0\n-7", serialize(&Day01::parse("+01\n-0\n-7\n").unwrap()));
+    }
 
-        let total = changes.iter()
-            .fold(0i32, |acc, change| {
-                match change.sign {
-                    Sign::Pos => acc + change.value as i32,
-                    Sign::Neg => acc - change.value as i32
-                }
-            });
+    #[test]
+    fn change_from_str() {
+        assert_eq!(Ok(Change { sign: Sign::Neg, value: 4 }), "-4".parse());
+        assert_eq!(
+            Ok(vec![Change::from(1), Change::from(-2)]),
+            "+1\n-2".lines().map(str::parse).collect::<Result<Vec<Change>, _>>()
+        );
+        assert_eq!(ErrorKind::TrailingInput, "+1 +2".parse::<Change>().unwrap_err().kind);
+    }
+
+    #[test]
+    fn changes_malformed_line() {
+        let error = Day01::parse("-456\n+123\nx\n+1").unwrap_err();
 
-        assert_eq!(538, total);
+        assert_eq!(ErrorKind::Expected("digit"), error.kind);
+        assert_eq!((3, 1), (error.line, error.column));
     }
 
     #[test]
-    fn part2(){
-        let input = fs::read_to_string("./src/day01/input.txt").unwrap();
+    fn changes_trailing_input() {
+        let error = Day01::parse("-456\n+123x +1\n").unwrap_err();
 
-        let changes : Vec<Change> = changes(CompleteStr(&input)).unwrap().1;
+        assert_eq!(ErrorKind::TrailingInput, error.kind);
+        assert_eq!((2, 5), (error.line, error.column));
+    }
 
-        let mut frequencies = HashSet::new();
-        let mut acc = 0;
+    #[test]
+    fn changes_overflow() {
+        let error = Day01::parse("+1\n-9223372036854775808\n+9223372036854775808").unwrap_err();
 
-        let mut frequency_reached_twice = None;
+        assert_eq!(ErrorKind::Overflow, error.kind);
+        assert_eq!((3, 1), (error.line, error.column));
+    }
 
-        while frequency_reached_twice.is_none() {
+    #[test]
+    fn changes_separators() {
+        let expected = [1, -2, 3, 1].iter().map(|&delta| Change::from(delta)).collect::<Vec<_>>();
 
-            for change in &changes {
+        for input in ["+1, -2, +3, +1", "+1 -2 +3 +1", "1,-2\n 3 ,\n+1\n", "+1\r\n-2\t+3, 1"].iter() {
+            assert_eq!(Ok(&expected), Day01::parse(input).as_ref(), "{:?}", input);
+        }
+        assert_eq!(Ok(vec![]), Day01::parse(""));
 
-                if frequencies.contains(&acc) {
-                    frequency_reached_twice = Some(acc);
-                    break;
-                } else {
-                    frequencies.insert(acc);
+        let error = Day01::parse("+1, -2,\n").unwrap_err();
+        assert_eq!(ErrorKind::Expected("digit"), error.kind);
+        assert_eq!((2, 1), (error.line, error.column));
+    }
+
+    #[test]
+    fn frequency_overflow() {
+        let changes = Day01::parse("-9223372036854775808, +9223372036854775807, +1").unwrap();
+
+        assert_eq!(Some(-9223372036854775808), resulting_frequency(&changes[..1]));
+        assert_eq!(Some(0), resulting_frequency(&changes));
+        assert_eq!(None, resulting_frequency(&changes[1..]));
+        assert_eq!(None, first_repeated_frequency(&changes[1..]));
+        assert_eq!(None, drift_analysis(&changes[1..]));
+
+        // the drift of -1 carries i64::MIN back onto itself after 2^63 cycles
+        let changes = Day01::parse("-9223372036854775808, +9223372036854775807").unwrap();
+        assert_eq!(Some(-9223372036854775808), first_repeated_frequency(&changes));
+        assert_eq!(Some(-1), drift_analysis(&changes).map(|analysis| analysis.drift));
+    }
+
+    #[test]
+    fn first_repeat_examples() {
+        let examples: [(&[i64], Option<i64>); 7] = [
+            (&[1, -2, 3, 1], Some(2)),
+            (&[1, -1], Some(0)),
+            (&[3, 3, 4, -2, -4], Some(10)),
+            (&[-6, 3, 8, 5, -6], Some(5)),
+            (&[7, 7, -2, -7, -4], Some(14)),
+            (&[1, 1, 1], None),
+            (&[], None),
+        ];
+
+        for (deltas, expected) in examples.iter() {
+            let changes = deltas.iter().map(|&delta| Change::from(delta)).collect::<Vec<_>>();
+
+            assert_eq!(*expected, first_repeated_frequency(&changes), "{:?}", deltas);
+        }
+    }
+
+    #[test]
+    fn first_repeat_matches_brute_force() {
+        let mut rng = Rng::new(1);
+
+        for _ in 0..2000 {
+            let len = 1 + rng.below(8) as usize;
+            let changes = (0..len)
+                .map(|_| Change::from(rng.below(21) as i64 - 10))
+                .collect::<Vec<_>>();
+
+            // frequencies in a cycle span at most 80, so any repeat is within 80 cycles
+            assert_eq!(
+                first_repeated_frequency_brute_force(&changes, 100),
+                first_repeated_frequency(&changes),
+                "{:?}", changes
+            );
+        }
+    }
+
+    #[test]
+    fn drift_analysis_example() {
+        let changes = [1, -2, 3, 1].iter().map(|&delta| Change::from(delta)).collect::<Vec<_>>();
+
+        assert_eq!(
+            Some(DriftAnalysis {
+                drift: 3,
+                min: -1,
+                max: 3,
+                repeats: vec![Repeat { frequency: 2, cycle: 1, index: 2 }],
+                cycles: Some(2),
+            }),
+            drift_analysis(&changes)
+        );
+        assert_eq!(
+            Some(DriftAnalysis { drift: 0, min: 0, max: 0, repeats: vec![], cycles: None }),
+            drift_analysis(&[])
+        );
+    }
+
+    #[test]
+    fn drift_analysis_matches_brute_force() {
+        let mut rng = Rng::new(2);
+
+        for _ in 0..2000 {
+            let len = 1 + rng.below(8) as usize;
+            let changes = (0..len)
+                .map(|_| Change::from(rng.below(21) as i64 - 10))
+                .collect::<Vec<_>>();
+            let analysis = drift_analysis(&changes).unwrap();
+
+            assert_eq!(repeats_brute_force(&changes, 100), analysis.repeats, "{:?}", changes);
+            assert_eq!(analysis.cycles.map(|cycles| cycles - 1), analysis.repeats.first().map(|repeat| repeat.cycle));
+        }
+    }
+
+    #[test]
+    fn reader_matches_parser() {
+        let inputs = [
+            "", "\n", "+1\n-2\n+3\n+1", "+1, -2 3\n\n ,4\r\n", "\n+1", "+1,\n", "+1,", "+1,\n\n",
+            "+1,,2", "+1 , x", "+1x\n+2", "+1\n  +2x", "+1\n+99999999999999999999\n", "1, 2,\n3 4 ,", ",1",
+        ];
+        for input in inputs.iter() {
+            assert_eq!(Day01::parse(input), read_changes(input), "{:?}", input);
+        }
+
+        let separators = ["\n", ", ", " ", ",\n", " \n ", "\r\n"];
+        let mut rng = Rng::new(3);
+        for seed in 0..200 {
+            let generated = generate::day01(&mut Rng::new(seed), 10);
+            let mut input = String::new();
+            for change in generated.lines() {
+                if !input.is_empty() {
+                    let separator = *rng.choose(&separators);
+                    input.push_str(separator);
                 }
+                input.push_str(change);
+            }
 
-                acc = match change.sign {
-                    Sign::Pos => acc + change.value as i32,
-                    Sign::Neg => acc - change.value as i32
-                };
+            if rng.chance(1, 2) {
+                let position = rng.below(input.len() as u64) as usize;
+                input.insert(position, *rng.choose(&['x', ',', '+', '-', ' ', '\n']));
             }
+
+            assert_eq!(Day01::parse(&input), read_changes(&input), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn reader_buffers_one_read() {
+        let input = vec!["+1"; 10_000].join(", ") + ", x";
+        let mut reader = ChangeReader::new(BufReader::with_capacity(64, input.as_bytes()));
+
+        for _ in 0..10_000 {
+            assert_eq!(Change::from(1), reader.next().unwrap().unwrap());
+            assert!(reader.buffer.len() <= 64 + MAX_TOKEN);
+        }
+        match reader.next() {
+            Some(Err(StreamError::Parse(e))) => {
+                assert_eq!((ErrorKind::Expected("digit"), 1, input.len()), (e.kind, e.line, e.column));
+            },
+            result => panic!("expected a parse error, got {:?}", result),
         }
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn read_frequencies() {
+        assert_eq!(Some(3), read_resulting_frequency("+1, -2, +3, +1".as_bytes()).unwrap());
+        assert_eq!(Some(2), read_first_repeated_frequency("+1\n-2\n+3\n+1\n".as_bytes()).unwrap());
+        assert_eq!(None, read_first_repeated_frequency("+1 +1".as_bytes()).unwrap());
+        assert_eq!(None, read_resulting_frequency("+9223372036854775807, +1, -1".as_bytes()).unwrap());
+
+        match read_resulting_frequency("+1\nx".as_bytes()) {
+            Err(StreamError::Parse(e)) => assert_eq!((ErrorKind::Expected("digit"), 2, 1), (e.kind, e.line, e.column)),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+        assert!(matches!(read_resulting_frequency(&b"+1\n\xff\n"[..]), Err(StreamError::Io(_))));
+    }
+
+    #[test]
+    fn part1(){
+        let input = input_or_skip!(1);
+
+        let changes : Vec<Change> = changes(CompleteStr(&input)).unwrap().1;
+
+        assert_eq!(Some(538), resulting_frequency(&changes));
+        assert_eq!(Some(538), read_resulting_frequency(input.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn part2(){
+        let input = input_or_skip!(1);
+
+        let changes : Vec<Change> = changes(CompleteStr(&input)).unwrap().1;
 
-        assert_eq!(Some(77271), frequency_reached_twice);
+        assert_eq!(Some(77271), first_repeated_frequency(&changes));
+        assert_eq!(Some(77271), first_repeated_frequency_brute_force(&changes, usize::MAX));
+        assert_eq!(Some(77271), read_first_repeated_frequency(input.as_bytes()).unwrap());
+        assert_eq!(Some(77271), drift_analysis(&changes).unwrap().repeats.first().map(|repeat| repeat.frequency));
     }
 }
@@ -1,60 +1,217 @@
-#[cfg(test)]
-mod tests {
-    use std::fs;
-    use std::collections::HashMap;
-
-    fn keep_common_letters(s1: &str, s2: &str) -> String {
-        s1.chars()
-            .zip(s2.chars())
-            .fold(String::new(),
-                  |mut acc, (c1, c2)| {
-                      if c1.eq(&c2) { acc.push(c1); }
-
-                      acc
-                  }
-            )
-    }
-
-    fn find_one_letter_off(input: String) -> Option<String> {
-        for (index, line) in input.lines().enumerate() {
-            for line2 in input.lines().skip(index) {
-                let common_letters = keep_common_letters(line, line2);
-                let char_diff = line.len() - common_letters.len();
-                if char_diff == 1 { return Some(common_letters); }
+use nom::{named, call, map, take_while};
+use nom::types::CompleteStr;
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::backend::{Backend, Hand, Nom, Parser};
+use crate::error::{ErrorKind, ParseError, finish};
+use crate::parsers::lines;
+use crate::solution::Solution;
+
+// a line ending may be either LF or CRLF, as with str::lines
+named!(
+    pub id<CompleteStr, String>,
+    map!(
+        take_while!(|c: char| c != '\n'),
+        |CompleteStr(line)| String::from(line.strip_suffix('\r').unwrap_or(line))
+    )
+);
+
+named!(
+    pub ids<CompleteStr, Vec<String> >,
+    call!(lines, id)
+);
+
+pub fn keep_common_letters(s1: &str, s2: &str) -> String {
+    s1.chars()
+        .zip(s2.chars())
+        .fold(String::new(),
+              |mut acc, (c1, c2)| {
+                  if c1.eq(&c2) { acc.push(c1); }
+
+                  acc
+              }
+        )
+}
+
+/// How many times each letter appears in an ID
+fn letter_counts(id: &str) -> HashMap<char, usize> {
+    let mut counts = HashMap::new();
+    for letter in id.chars() {
+        *counts.entry(letter).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+/// Returned when an ID is a different length to the first
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LengthMismatch {
+    /// Position of the ID in the list
+    pub index: usize,
+    /// Length of the first ID, in characters
+    pub expected: usize,
+    /// Length of the mismatched ID, in characters
+    pub found: usize,
+}
+
+impl fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "box ID {} has {} letters but the first has {}", self.index + 1, self.found, self.expected)
+    }
+}
+
+impl std::error::Error for LengthMismatch {}
+
+impl LengthMismatch {
+    /// Points at the first letter past the expected length of the ID, or the end of
+    /// a short one, in the input it was parsed from
+    fn locate(&self, input: &str) -> ParseError {
+        let line_start = input.split('\n').take(self.index).map(|line| line.len() + 1).sum::<usize>();
+        let line = input[line_start..].split('\n').next().unwrap_or("");
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        let (offset, expected) = match line.char_indices().nth(self.expected) {
+            Some((offset, _)) => (offset, "end of line"),
+            None => (line.len(), "letter"),
+        };
+
+        ParseError::at(input, line_start + offset, ErrorKind::Expected(expected))
+    }
+}
+
+/// A list of box IDs which all have the same length
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<String>", into = "Vec<String>"))]
+pub struct BoxIds {
+    ids: Vec<String>,
+}
+
+impl BoxIds {
+    pub fn new(ids: Vec<String>) -> Result<Self, LengthMismatch> {
+        let expected = ids.first().map_or(0, |id| id.chars().count());
+
+        for (index, id) in ids.iter().enumerate() {
+            let found = id.chars().count();
+            if found != expected {
+                return Err(LengthMismatch { index, expected, found });
             }
         }
 
-        None
+        Ok(BoxIds { ids })
     }
 
-    #[test]
-    fn part1() {
-        let input = fs::read_to_string("./src/day02/input.txt").unwrap();
-        let mut has_two_count = 0;
-        let mut has_three_count = 0;
-
-        for line in input.lines() {
-            let mut letter_quantity = HashMap::new();
-            for letter in line.chars() {
-                match letter_quantity.get(&letter) {
-                    Some(quantity) => letter_quantity.insert(letter, quantity + 1),
-                    None => letter_quantity.insert(letter, 1),
-                };
-            }
+    pub fn ids(&self) -> &[String] {
+        &self.ids
+    }
+
+    /// Multiplies the number of IDs with a letter appearing exactly twice by the
+    /// number with a letter appearing exactly three times
+    pub fn checksum(&self) -> u64 {
+        self.checksum_with(&[2, 3])
+    }
+
+    /// Multiplies together, for each count, the number of IDs with a letter
+    /// appearing exactly that many times, or returns 0 if no counts are given
+    pub fn checksum_with(&self, counts: &[usize]) -> u64 {
+        if counts.is_empty() {
+            return 0;
+        }
 
-            let mut has_two = false;
-            let mut has_three = false;
+        let mut totals = vec![0u64; counts.len()];
 
-            for &quanity in letter_quantity.values() {
-                if quanity == 2 { has_two = true; }
-                if quanity == 3 { has_three = true; }
+        for id in &self.ids {
+            let letter_counts = letter_counts(id);
+
+            for (total, count) in totals.iter_mut().zip(counts) {
+                if letter_counts.values().any(|n| n == count) {
+                    *total += 1;
+                }
             }
+        }
+
+        totals.iter().product()
+    }
 
-            if has_two { has_two_count += 1; }
-            if has_three { has_three_count += 1; }
+    /// Finds two IDs which differ by exactly one letter, returning the letters they
+    /// have in common
+    pub fn find_near_match(&self) -> Option<String> {
+        for (index, id) in self.ids.iter().enumerate() {
+            for other in self.ids.iter().skip(index + 1) {
+                let common_letters = keep_common_letters(id, other);
+                if id.chars().count() - common_letters.chars().count() == 1 {
+                    return Some(common_letters);
+                }
+            }
         }
 
-        assert_eq!(7688, has_two_count * has_three_count);
+        None
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Vec<String>> for BoxIds {
+    type Error = LengthMismatch;
+
+    fn try_from(ids: Vec<String>) -> Result<Self, Self::Error> {
+        BoxIds::new(ids)
+    }
+}
+
+impl From<BoxIds> for Vec<String> {
+    fn from(ids: BoxIds) -> Self {
+        ids.ids
+    }
+}
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+
+    type Input = BoxIds;
+    type Part1 = u64;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        <Self as Parser<Backend>>::parse_input(input)
+    }
+
+    fn part1(ids: &Self::Input) -> Option<Self::Part1> {
+        Some(ids.checksum())
+    }
+
+    fn part2(ids: &Self::Input) -> Option<Self::Part2> {
+        ids.find_near_match()
+    }
+}
+
+impl Parser<Nom> for Day02 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        let ids = finish(input, ids(CompleteStr(input)))?;
+        BoxIds::new(ids).map_err(|e| e.locate(input))
+    }
+}
+
+impl Parser<Hand> for Day02 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        let ids = input.lines().map(String::from).collect();
+        BoxIds::new(ids).map_err(|e| e.locate(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1() {
+        let ids = Day02::parse(&input_or_skip!(2)).unwrap();
+
+        assert_eq!(7688, ids.checksum());
     }
 
     #[test]
@@ -67,10 +224,60 @@ mod tests {
         assert_eq!(String::from("ac"), keep_common_letters("abc", "azc"));
     }
 
+    fn box_ids(ids: &[&str]) -> BoxIds {
+        BoxIds::new(ids.iter().map(|&id| String::from(id)).collect()).unwrap()
+    }
+
+    #[test]
+    fn checksum_example() {
+        let ids = box_ids(&["abcdef", "bababc", "abbcde", "abcccd", "aabcdd", "abcdee", "ababab"]);
+
+        assert_eq!(12, ids.checksum());
+        assert_eq!(4, ids.checksum_with(&[2]));
+        assert_eq!(3, ids.checksum_with(&[3]));
+        assert_eq!(0, ids.checksum_with(&[2, 3, 4]));
+        assert_eq!(6, ids.checksum_with(&[1]));
+        assert_eq!(0, ids.checksum_with(&[]));
+    }
+
+    #[test]
+    fn find_near_match_example() {
+        let ids = box_ids(&["abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz"]);
+
+        assert_eq!(Some(String::from("fgij")), ids.find_near_match());
+        assert_eq!(None, box_ids(&["abc", "abc", "xyz"]).find_near_match());
+    }
+
+    #[test]
+    fn ids_different_lengths() {
+        assert_eq!(
+            Err(LengthMismatch { index: 2, expected: 3, found: 1 }),
+            BoxIds::new(vec![String::from("abc"), String::from("xyz"), String::from("a")])
+        );
+        assert_eq!(Ok(box_ids(&[])), BoxIds::new(vec![]));
+
+        let error = Day02::parse("abc\nabcd\nab").unwrap_err();
+        assert_eq!((ErrorKind::Expected("end of line"), 2, 4), (error.kind, error.line, error.column));
+
+        let error = Day02::parse("abc\r\nabc\r\nab\r\n").unwrap_err();
+        assert_eq!((ErrorKind::Expected("letter"), 3, 3), (error.kind, error.line, error.column));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let ids = box_ids(&["abc", "abd"]);
+        let json = serde_json::to_string(&ids).unwrap();
+
+        assert_eq!(r#"["abc","abd"]"#, json);
+        assert_eq!(ids, serde_json::from_str(&json).unwrap());
+        assert!(serde_json::from_str::<BoxIds>(r#"["abc","ab"]"#).is_err());
+    }
+
     #[test]
     fn part2() {
-        let input = fs::read_to_string("./src/day02/input.txt").unwrap();
+        let ids = Day02::parse(&input_or_skip!(2)).unwrap();
 
-        assert_eq!(Some(String::from("lsrivmotzbdxpkxnaqmuwcchj")), find_one_letter_off(input));
+        assert_eq!(Some(String::from("lsrivmotzbdxpkxnaqmuwcchj")), ids.find_near_match());
     }
 }
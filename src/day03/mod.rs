@@ -1,102 +1,160 @@
-use nom::{named, do_parse, separated_list, call, error_position, eol, map_res, tag, recognize};
+use nom::{named, do_parse, call, tag};
 use nom::types::CompleteStr;
+use std::fmt;
 use std::str::FromStr;
-use std::collections::HashMap;
+
+use crate::backend::{Backend, Hand, Nom, Parser, parse_item};
+use crate::error::{ParseError, finish};
+use crate::grid::{Grid, SizeError};
+use crate::parsers::{lines, unsigned};
+use crate::scanner::{Scanner, complete};
+use crate::solution::Solution;
 
 #[derive(Debug, PartialEq)]
-struct FabricSection {
-    id: u16,
-    left_pos: u16,
-    top_pos: u16,
-    width: u16,
-    height: u16,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FabricSection {
+    pub id: u16,
+    pub left_pos: u16,
+    pub top_pos: u16,
+    pub width: u16,
+    pub height: u16,
 }
 
-named!(
-    take_u16<CompleteStr, u16>,
-    map_res!(recognize!(nom::digit), |CompleteStr(s)| u16::from_str(s))
-);
+/// Writes the claim in the same form as the puzzle input
+impl fmt::Display for FabricSection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} @ {},{}: {}x{}", self.id, self.left_pos, self.top_pos, self.width, self.height)
+    }
+}
 
 named!(
-    fabric_section<CompleteStr, FabricSection>,
+    pub fabric_section<CompleteStr, FabricSection>,
     do_parse!(
         tag!("#") >>
-        id: take_u16 >>
+        id: call!(unsigned::<u16>) >>
         tag!(" @ ") >>
-        left_pos: take_u16 >>
+        left_pos: call!(unsigned::<u16>) >>
         tag!(",") >>
-        top_pos: take_u16 >>
+        top_pos: call!(unsigned::<u16>) >>
         tag!(": ") >>
-        width: take_u16 >>
+        width: call!(unsigned::<u16>) >>
         tag!("x") >>
-        height: take_u16 >>
+        height: call!(unsigned::<u16>) >>
         (FabricSection { id, left_pos, top_pos, width, height })
     )
 );
 
 named!(
-    fabric_sections<CompleteStr, Vec<FabricSection> >,
-    separated_list!(eol, fabric_section)
+    pub fabric_sections<CompleteStr, Vec<FabricSection> >,
+    call!(lines, fabric_section)
 );
 
-fn calc_used_fabric(sections: &Vec<FabricSection>) -> HashMap<(u16, u16), u16> {
-    // map tuple of left_pos, top_pos fabric location to quantity of sections which use it
-    let mut used_fabric = HashMap::new();
+fn scan_fabric_section(scanner: &mut Scanner) -> Result<FabricSection, ParseError> {
+    scanner.tag("#")?;
+    let id = scanner.unsigned()?;
+    scanner.tag(" @ ")?;
+    let left_pos = scanner.unsigned()?;
+    scanner.tag(",")?;
+    let top_pos = scanner.unsigned()?;
+    scanner.tag(": ")?;
+    let width = scanner.unsigned()?;
+    scanner.tag("x")?;
+    let height = scanner.unsigned()?;
 
-    for section in sections {
-        let &FabricSection {
-            left_pos, top_pos, width, height, ..
-        } = section;
-
-        for left in left_pos..(left_pos+width) {
-            for top in top_pos..(top_pos+height) {
-                let count = used_fabric
-                    .entry((left, top))
-                    .or_insert(0);
-                *count += 1;
-            }
-        }
+    Ok(FabricSection { id, left_pos, top_pos, width, height })
+}
+
+impl FromStr for FabricSection {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_item(s, fabric_section, scan_fabric_section)
     }
+}
+
+impl FabricSection {
+    /// Coordinates of every square inch of fabric covered by the section
+    pub fn coords(&self) -> impl Iterator<Item = (i64, i64)> {
+        let left = i64::from(self.left_pos);
+        let top = i64::from(self.top_pos);
+        let (width, height) = (i64::from(self.width), i64::from(self.height));
 
-    used_fabric
+        (left..left + width).flat_map(move |x| (top..top + height).map(move |y| (x, y)))
+    }
 }
 
-fn calc_double_used_fabric(used_fabric: HashMap<(u16, u16), u16>) -> u64 {
-    let mut double_used = 0;
+/// Maps each square inch of fabric covered by a section to the quantity of sections
+/// which use it
+///
+/// The grid only spans the sections, so sections far from the top left corner don't
+/// need a grid reaching all the way back to it.
+pub fn calc_used_fabric(sections: &[FabricSection]) -> Result<Grid<u32>, SizeError> {
+    let left = sections.iter().map(|s| i64::from(s.left_pos)).min().unwrap_or(0);
+    let top = sections.iter().map(|s| i64::from(s.top_pos)).min().unwrap_or(0);
+    let right = sections.iter().map(|s| i64::from(s.left_pos) + i64::from(s.width)).max().unwrap_or(0);
+    let bottom = sections.iter().map(|s| i64::from(s.top_pos) + i64::from(s.height)).max().unwrap_or(0);
+    let mut used_fabric = Grid::with_origin((left, top), (right - left) as usize, (bottom - top) as usize, 0)?;
 
-    for &usages in used_fabric.values() {
-        if usages > 1 { double_used += 1; }
+    for section in sections {
+        for coord in section.coords() {
+            used_fabric[coord] += 1;
+        }
     }
 
-    double_used
+    Ok(used_fabric)
 }
 
-fn find_free_section(sections: Vec<FabricSection>, used_fabric: HashMap<(u16, u16), u16>) -> Option<u16> {
-    'outer: for section in sections {
-        let FabricSection {
-            left_pos, top_pos, width, height, ..
-        } = section;
-
-        for left in left_pos..(left_pos+width) {
-            for top in top_pos..(top_pos+height) {
-                let &count = used_fabric
-                    .get(&(left, top))
-                    .unwrap();
-                if count != 1 { continue 'outer }
-            }
-        }
+pub fn calc_double_used_fabric(used_fabric: &Grid<u32>) -> u64 {
+    used_fabric.fold(0, |double_used, _, &usages| {
+        if usages > 1 { double_used + 1 } else { double_used }
+    })
+}
+
+pub fn find_free_section(sections: &[FabricSection], used_fabric: &Grid<u32>) -> Option<u16> {
+    sections.iter()
+        .find(|section| section.coords().all(|coord| used_fabric[coord] == 1))
+        .map(|section| section.id)
+}
 
-        return Some(section.id);
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+
+    type Input = Vec<FabricSection>;
+    type Part1 = u64;
+    type Part2 = u16;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        <Self as Parser<Backend>>::parse_input(input)
+    }
 
+    fn part1(sections: &Self::Input) -> Option<Self::Part1> {
+        calc_used_fabric(sections).ok().map(|used_fabric| calc_double_used_fabric(&used_fabric))
     }
 
-    None
+    fn part2(sections: &Self::Input) -> Option<Self::Part2> {
+        find_free_section(sections, &calc_used_fabric(sections).ok()?)
+    }
+}
+
+impl Parser<Nom> for Day03 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        finish(input, fabric_sections(CompleteStr(input)))
+    }
+}
+
+impl Parser<Hand> for Day03 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        complete(input, |scanner| scanner.lines(scan_fabric_section))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::generate::{self, Rng};
+    use crate::error::ErrorKind;
 
     #[test]
     fn fabric_section_parse() {
@@ -124,33 +182,107 @@ mod tests {
         assert_eq!(expected, fabric_sections(input).unwrap().1);
     }
 
+    #[test]
+    fn fabric_section_parse_error() {
+        let error = Day03::parse("#1 @ 286,440: 19x24\n#2 @ 430;120: 20x14").unwrap_err();
+
+        assert_eq!(ErrorKind::Expected("literal text"), error.kind);
+        assert_eq!((2, 9), (error.line, error.column));
+        assert_eq!("#2 @ 430;120: 20x14", error.line_text);
+    }
+
+    #[test]
+    fn fabric_section_overflow() {
+        let error = Day03::parse("#1 @ 286,440: 19x24\n#2 @ 430,120: 70000x14").unwrap_err();
+
+        assert_eq!(ErrorKind::Overflow, error.kind);
+        assert_eq!((2, 15), (error.line, error.column));
+    }
+
+    #[test]
+    fn parse_serialize_round_trip() {
+        generate::assert_round_trip::<Day03>(0..20, generate::day03, |_| 50, |sections| generate::lines(sections));
+    }
+
+    #[test]
+    fn fabric_section_from_str() {
+        assert_eq!(
+            Ok(FabricSection { id: 1, left_pos: 1, top_pos: 3, width: 4, height: 4 }),
+            "#1 @ 1,3: 4x4".parse()
+        );
+
+        let error = "#1 @ 1,3: 4x".parse::<FabricSection>().unwrap_err();
+        assert_eq!((ErrorKind::Expected("digit"), 13), (error.kind, error.column));
+    }
+
     #[test]
     fn map_used_fabric_single() {
         let input = CompleteStr("#1 @ 1,3: 1x1");
         let sections = fabric_sections(input).unwrap().1;
 
-        let used_fabric = calc_used_fabric(&sections);
+        let used_fabric = calc_used_fabric(&sections).unwrap();
+
+        assert_eq!(Some(&1), used_fabric.get((1, 3)));
+    }
+
+    #[test]
+    fn map_used_fabric_far_from_origin() {
+        let sections = Day03::parse("#1 @ 65531,65531: 5x5\n#2 @ 65530,65532: 2x2").unwrap();
+        let used_fabric = calc_used_fabric(&sections).unwrap();
 
-        assert_eq!(Some(&1), used_fabric.get(&(1, 3)));
+        assert_eq!((65530, 65531), used_fabric.origin());
+        assert_eq!((6, 5), (used_fabric.width(), used_fabric.height()));
+        assert_eq!(Some(2), Day03::part1(&sections));
+        assert_eq!(None, Day03::part2(&sections));
+    }
+
+    #[test]
+    fn map_used_fabric_many_overlaps() {
+        let mut input = vec!["#1 @ 0,0: 1x1"; 65536];
+        input.push("#2 @ 1,0: 1x1");
+        let sections = Day03::parse(&input.join("\n")).unwrap();
+
+        assert_eq!(65536, calc_used_fabric(&sections).unwrap()[(0, 0)]);
+        assert_eq!(Some(1), Day03::part1(&sections));
+        assert_eq!(Some(2), Day03::part2(&sections));
     }
 
     #[test]
     fn part1() {
-        let input = fs::read_to_string("./src/day03/input.txt").unwrap();
+        let input = input_or_skip!(3);
         let sections = fabric_sections(CompleteStr(&input)).unwrap().1;
 
-        let used_fabric = calc_used_fabric(&sections);
+        let used_fabric = calc_used_fabric(&sections).unwrap();
 
-        assert_eq!(115242, calc_double_used_fabric(used_fabric));
+        assert_eq!(115242, calc_double_used_fabric(&used_fabric));
     }
 
     #[test]
     fn part2() {
-        let input = fs::read_to_string("./src/day03/input.txt").unwrap();
+        let input = input_or_skip!(3);
         let sections = fabric_sections(CompleteStr(&input)).unwrap().1;
 
-        let used_fabric = calc_used_fabric(&sections);
+        let used_fabric = calc_used_fabric(&sections).unwrap();
+
+        assert_eq!(Some(1046), find_free_section(&sections, &used_fabric));
+    }
 
-        assert_eq!(Some(1046), find_free_section(sections, used_fabric));
+    #[test]
+    fn double_used_fabric_matches_naive_count() {
+        for seed in 0..20 {
+            let input = generate::day03(&mut Rng::new(seed), 3 + seed as usize * 2);
+            let sections = Day03::parse(&input).unwrap();
+
+            let contains = |section: &FabricSection, (x, y): (u16, u16)| {
+                x >= section.left_pos && x < section.left_pos + section.width &&
+                    y >= section.top_pos && y < section.top_pos + section.height
+            };
+            let naive = (0..200u16)
+                .flat_map(|x| (0..200u16).map(move |y| (x, y)))
+                .filter(|&cell| sections.iter().filter(|section| contains(section, cell)).count() > 1)
+                .count() as u64;
+
+            assert_eq!(naive, calc_double_used_fabric(&calc_used_fabric(&sections).unwrap()), "seed {}", seed);
+        }
     }
 }
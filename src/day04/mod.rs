@@ -1,62 +1,77 @@
-use nom::{named, do_parse, call, error_position, eol, map_res, tag, take, alt, terminated, tuple, tuple_parser, separated_list, eof, recognize};
+use nom::{named, do_parse, call, tag, alt};
 use nom::types::CompleteStr;
 
-use std::str::FromStr;
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::backend::{Backend, Hand, Nom, Parser, parse_item};
+use crate::error::{ErrorKind, ParseError, finish};
+use crate::parsers::{fixed, lines, unsigned};
+use crate::scanner::{Scanner, complete};
+use crate::solution::Solution;
 
 // datetime is much better off handled by the Chrono crate in a production application
 #[derive(Debug, PartialEq, Ord, PartialOrd, Eq)]
-struct DateTime {
-    year: u16,
-    month: u8,
-    day: u8,
-    hour: u8,
-    minute: u8
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8
 }
 
 named!(
-    parse_u16<CompleteStr, u16>,
-    map_res!(take!(4), |CompleteStr(s)| u16::from_str(s))
-);
-
-named!(
-    parse_u8<CompleteStr, u8>,
-    map_res!(take!(2), |CompleteStr(s)| u8::from_str(s))
-);
-
-named!(
-    dt<CompleteStr, DateTime>,
+    pub dt<CompleteStr, DateTime>,
     do_parse!(
-        year: parse_u16 >>
+        year: call!(fixed::<u16>, 4) >>
         tag!("-") >>
-        month: parse_u8 >>
+        month: call!(fixed::<u8>, 2) >>
         tag!("-") >>
-        day: parse_u8 >>
+        day: call!(fixed::<u8>, 2) >>
         tag!(" ") >>
-        hour: parse_u8 >>
+        hour: call!(fixed::<u8>, 2) >>
         tag!(":") >>
-        minute: parse_u8 >>
+        minute: call!(fixed::<u8>, 2) >>
         ( DateTime {year, month, day, hour, minute} )
     )
 );
 
-type GuardId = u16;
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02} {:02}:{:02}", self.year, self.month, self.day, self.hour, self.minute)
+    }
+}
+
+pub type GuardId = u16;
 
 #[derive(Debug, PartialEq)]
-enum Action {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
     FallsAsleep,
     WakesUp,
     StartsShift(GuardId),
 }
 
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::FallsAsleep => write!(f, "falls asleep"),
+            Action::WakesUp => write!(f, "wakes up"),
+            Action::StartsShift(id) => write!(f, "Guard #{} begins shift", id),
+        }
+    }
+}
+
 named!(
-    action<CompleteStr, Action>,
+    pub action<CompleteStr, Action>,
     alt!(
         tag!("falls asleep") => { |_| Action::FallsAsleep } |
         tag!("wakes up") => { |_| Action::WakesUp } |
         do_parse!(
             tag!("Guard #") >>
-            id: map_res!(recognize!(nom::digit), |CompleteStr(s)| u16::from_str(s)) >>
+            id: call!(unsigned::<GuardId>) >>
             tag!(" begins shift") >>
             ( Action::StartsShift(id) )
         )
@@ -64,13 +79,21 @@ named!(
 );
 
 #[derive(Debug, PartialEq)]
-struct Event {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event {
     pub datetime: DateTime,
     pub action: Action
 }
 
+/// Writes the event as a line of the puzzle input
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}", self.datetime, self.action)
+    }
+}
+
 named!(
-    event<CompleteStr, Event>,
+    pub event<CompleteStr, Event>,
     do_parse!(
         tag!("[") >>
         datetime: dt >>
@@ -81,59 +104,132 @@ named!(
 );
 
 named!(
-    events<CompleteStr, Vec<Event> >,
-    separated_list!(eol, event)
+    pub events<CompleteStr, Vec<Event> >,
+    call!(lines, event)
 );
 
-struct Nap {
-    start_min: u8,
-    end_min: u8,
+fn scan_dt(scanner: &mut Scanner) -> Result<DateTime, ParseError> {
+    let year = scanner.fixed(4)?;
+    scanner.tag("-")?;
+    let month = scanner.fixed(2)?;
+    scanner.tag("-")?;
+    let day = scanner.fixed(2)?;
+    scanner.tag(" ")?;
+    let hour = scanner.fixed(2)?;
+    scanner.tag(":")?;
+    let minute = scanner.fixed(2)?;
+
+    Ok(DateTime { year, month, day, hour, minute })
+}
+
+fn scan_shift_start(scanner: &mut Scanner) -> Result<Action, ParseError> {
+    scanner.tag("Guard #")?;
+    let id = scanner.unsigned()?;
+    scanner.tag(" begins shift")?;
+
+    Ok(Action::StartsShift(id))
+}
+
+fn scan_action(scanner: &mut Scanner) -> Result<Action, ParseError> {
+    if scanner.eat("falls asleep") {
+        return Ok(Action::FallsAsleep);
+    }
+    if scanner.eat("wakes up") {
+        return Ok(Action::WakesUp);
+    }
+
+    // as with alt!, a failed alternative is reported at the start of the action,
+    // unless its number was out of range
+    let start = scanner.offset();
+    scan_shift_start(scanner).map_err(|error| match error.kind {
+        ErrorKind::Overflow => error,
+        _ => scanner.error_at(start, ErrorKind::Expected("one of several alternatives")),
+    })
+}
+
+fn scan_event(scanner: &mut Scanner) -> Result<Event, ParseError> {
+    scanner.tag("[")?;
+    let datetime = scan_dt(scanner)?;
+    scanner.tag("] ")?;
+    let action = scan_action(scanner)?;
+
+    Ok(Event { datetime, action })
+}
+
+impl FromStr for Event {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_item(s, event, scan_event)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nap {
+    pub start_min: u8,
+    pub end_min: u8,
 }
 
 impl Nap {
-    fn duration(&self) -> u8 {
+    pub fn duration(&self) -> u8 {
         self.end_min - self.start_min
     }
 }
 
-fn calc_naps(mut events: Vec<Event>) -> HashMap<GuardId, Vec<Nap>> {
+/// Groups the naps in the log by guard, or returns `None` if the log doesn't start
+/// with a guard starting their shift, has a minute past 59, or has a guard waking up
+/// without falling asleep first
+pub fn calc_naps(events: &[Event]) -> Option<HashMap<GuardId, Vec<Nap>>> {
     let mut naps = HashMap::new();
+    let mut events = events.iter().collect::<Vec<&Event>>();
     events.sort_by(|a, b| a.datetime.cmp(&b.datetime));
 
-    let mut active_guard = match events.get(0).unwrap().action {
+    let mut active_guard = match events.first()?.action {
         Action::StartsShift(guard_id) => guard_id,
-        _ => panic!("first event must be start shift event")
+        _ => return None,
     };
 
-    let mut nap_start_min = 0; // assume data is valid and wakes up is always preceded by falls asleep
+    let mut nap_start_min = None;
+
+    for event in events {
+        let minute = event.datetime.minute;
+        if minute >= 60 {
+            return None;
+        }
 
-    events.iter().for_each(|event| {
         match event.action {
             Action::WakesUp => {
+                let start_min = nap_start_min.take().filter(|&start_min| start_min <= minute)?;
                 let naps_for_active_guard = naps
                     .entry(active_guard)
                     .or_insert(vec![]);
-                naps_for_active_guard.push(Nap {start_min: nap_start_min, end_min: event.datetime.minute});
+                naps_for_active_guard.push(Nap {start_min, end_min: minute});
+            },
+            Action::FallsAsleep => nap_start_min = Some(minute),
+            Action::StartsShift(guard_id) => {
+                active_guard = guard_id;
+                nap_start_min = None;
             },
-            Action::FallsAsleep => nap_start_min = event.datetime.minute,
-            Action::StartsShift(guard_id) => active_guard = guard_id,
         };
-    });
+    }
 
-    naps
+    Some(naps)
 }
 
-fn most_time_napping(naps_per_guard: &HashMap<GuardId, Vec<Nap>>) -> GuardId {
-    *naps_per_guard.iter()
-        .max_by_key(|&(guard_id, naps)| total_nap_time(naps)).unwrap().0
+/// The guard who naps for longest, or `None` if no guard naps
+pub fn most_time_napping(naps_per_guard: &HashMap<GuardId, Vec<Nap>>) -> Option<GuardId> {
+    naps_per_guard.iter()
+        .max_by_key(|&(_guard_id, naps)| total_nap_time(naps))
+        .map(|(&guard_id, _naps)| guard_id)
 }
 
-fn total_nap_time(naps: &Vec<Nap>) -> u16 {
+pub fn total_nap_time(naps: &[Nap]) -> u32 {
     naps.iter()
-        .fold(0u16, |acc, nap| acc + nap.duration() as u16 )
+        .fold(0u32, |acc, nap| acc + u32::from(nap.duration()))
 }
 
-fn most_common_minute(naps: &Vec<Nap>) -> (u8, u8) {
+/// The minute the naps most often cover, and how many of them cover it
+pub fn most_common_minute(naps: &[Nap]) -> (u8, u32) {
     let mut minute_counts = [0u32; 60];
 
     for nap in naps {
@@ -143,10 +239,12 @@ fn most_common_minute(naps: &Vec<Nap>) -> (u8, u8) {
     }
 
     let (minute, count) = minute_counts.iter().enumerate().max_by_key(|&(_, &item)| item).unwrap();
-    (minute as u8, *count as u8)
+    (minute as u8, *count)
 }
 
-fn part2_impl(naps_per_guard: &HashMap<GuardId, Vec<Nap>>) -> (GuardId, u8) {
+/// The guard most often asleep on the same minute, and that minute, or `None` if no
+/// guard naps
+pub fn part2_impl(naps_per_guard: &HashMap<GuardId, Vec<Nap>>) -> Option<(GuardId, u8)> {
     // assuming there is a guard who stands above the rest
     let mut max_guard_id = 0;
     let mut max_minute = 0;
@@ -157,13 +255,97 @@ fn part2_impl(naps_per_guard: &HashMap<GuardId, Vec<Nap>>) -> (GuardId, u8) {
         if count > max_count { max_count = count; max_guard_id = guard_id; max_minute = minute; }
     }
 
-    (max_guard_id, max_minute)
+    if max_count == 0 { return None }
+
+    Some((max_guard_id, max_minute))
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+
+    type Input = Vec<Event>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        <Self as Parser<Backend>>::parse_input(input)
+    }
+
+    fn part1(events: &Self::Input) -> Option<Self::Part1> {
+        let naps_per_guard = calc_naps(events)?;
+        let guard_id = most_time_napping(&naps_per_guard)?;
+        let (minute, _count) = most_common_minute(naps_per_guard.get(&guard_id)?);
+
+        Some(u32::from(guard_id) * u32::from(minute))
+    }
+
+    fn part2(events: &Self::Input) -> Option<Self::Part2> {
+        let (guard_id, minute) = part2_impl(&calc_naps(events)?)?;
+
+        Some(u32::from(guard_id) * u32::from(minute))
+    }
+}
+
+impl Parser<Nom> for Day04 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        finish(input, events(CompleteStr(input)))
+    }
+}
+
+impl Parser<Hand> for Day04 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        complete(input, |scanner| scanner.lines(scan_event))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::generate;
+
+    #[test]
+    fn parse_serialize_round_trip() {
+        generate::assert_round_trip::<Day04>(0..20, generate::day04, |_| 30, |events| generate::lines(events));
+    }
+
+    #[test]
+    fn event_from_str() {
+        let event = "[1518-11-01 00:05] falls asleep".parse::<Event>().unwrap();
+
+        assert_eq!(Action::FallsAsleep, event.action);
+        assert_eq!(5, event.datetime.minute);
+        assert_eq!(
+            ErrorKind::Expected("one of several alternatives"),
+            "[1518-11-01 00:05] dozes off".parse::<Event>().unwrap_err().kind
+        );
+    }
+
+    #[test]
+    fn serialize_pads_fields() {
+        let event = Event {
+            datetime: DateTime { year: 518, month: 3, day: 9, hour: 0, minute: 5 },
+            action: Action::StartsShift(7),
+        };
+
+        assert_eq!("[0518-03-09 00:05] Guard #7 begins shift", event.to_string());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn naps_to_json() {
+        let events = Day04::parse("[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep\n[1518-11-01 00:25] wakes up").unwrap();
+
+        assert_eq!(
+            r#"{"10":[{"start_min":5,"end_min":25}]}"#,
+            serde_json::to_string(&calc_naps(&events).unwrap()).unwrap()
+        );
+        assert_eq!(
+            r#"{"datetime":{"year":1518,"month":11,"day":1,"hour":0,"minute":0},"action":{"StartsShift":10}}"#,
+            serde_json::to_string(&events[0]).unwrap()
+        );
+    }
 
     #[test]
     fn parse_dt() {
@@ -255,9 +437,9 @@ mod tests {
         let parsed_events : Vec<Event> = events(input).unwrap().1;
         assert_eq!(17, parsed_events.len());
 
-        let naps_per_guard: HashMap<GuardId, Vec<Nap>> = calc_naps(parsed_events);
+        let naps_per_guard: HashMap<GuardId, Vec<Nap>> = calc_naps(&parsed_events).unwrap();
 
-        let guard_id_most_time_napping = most_time_napping(&naps_per_guard);
+        let guard_id_most_time_napping = most_time_napping(&naps_per_guard).unwrap();
 
         assert_eq!(10, guard_id_most_time_napping);
 
@@ -270,12 +452,12 @@ mod tests {
 
     #[test]
     fn part1() {
-        let input = fs::read_to_string("./src/day04/input.txt").unwrap();
+        let input = input_or_skip!(4);
         let parsed_events = events(CompleteStr(&input)).unwrap().1;
 
-        let naps_per_guard: HashMap<GuardId, Vec<Nap>> = calc_naps(parsed_events);
+        let naps_per_guard: HashMap<GuardId, Vec<Nap>> = calc_naps(&parsed_events).unwrap();
 
-        let guard_id_most_time_napping = most_time_napping(&naps_per_guard);
+        let guard_id_most_time_napping = most_time_napping(&naps_per_guard).unwrap();
 
         assert_eq!(1487, guard_id_most_time_napping);
 
@@ -287,6 +469,56 @@ mod tests {
         assert_eq!(50558, 1487 * 34);
     }
 
+    #[test]
+    fn no_answer_for_degenerate_logs() {
+        let no_shift = Day04::parse("[1518-11-01 00:05] falls asleep\n[1518-11-01 00:25] wakes up").unwrap();
+        let no_naps = Day04::parse("[1518-11-01 00:00] Guard #10 begins shift").unwrap();
+        let past_the_hour = Day04::parse(
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep\n[1518-11-01 00:75] wakes up"
+        ).unwrap();
+        let wakes_before_sleeping = Day04::parse(
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:30] falls asleep\n[1518-11-01 00:20] wakes up"
+        ).unwrap();
+        let wakes_without_sleeping = Day04::parse(
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:20] wakes up"
+        ).unwrap();
+
+        for events in [vec![], no_shift, no_naps, past_the_hour, wakes_before_sleeping, wakes_without_sleeping].iter() {
+            assert_eq!(None, Day04::part1(events), "{:?}", events);
+            assert_eq!(None, Day04::part2(events), "{:?}", events);
+        }
+    }
+
+    /// A log with one shift a year for each guard, in which they nap once from
+    /// `start` until `end`
+    fn yearly_naps(shifts: &[(GuardId, u8, u8)]) -> Vec<Event> {
+        let log = shifts.iter().enumerate()
+            .map(|(year, (guard_id, start, end))| format!(
+                "[{0}-11-01 00:00] Guard #{1} begins shift\n[{0}-11-01 00:{2:02}] falls asleep\n[{0}-11-01 00:{3:02}] wakes up",
+                1000 + year, guard_id, start, end
+            ))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        Day04::parse(&log).unwrap()
+    }
+
+    #[test]
+    fn many_naps() {
+        let mut shifts = vec![(1, 0, 59); 1200];
+        shifts.push((1, 30, 31));
+        let events = yearly_naps(&shifts);
+        let naps_per_guard = calc_naps(&events).unwrap();
+
+        assert_eq!(70801, total_nap_time(&naps_per_guard[&1]));
+        assert_eq!((30, 1201), most_common_minute(&naps_per_guard[&1]));
+        assert_eq!(Some(30), Day04::part1(&events));
+
+        let mut shifts = vec![(1, 10, 11); 256];
+        shifts.push((2, 20, 21));
+        assert_eq!(Some(10), Day04::part2(&yearly_naps(&shifts)));
+    }
+
     #[test]
     fn part2_example() {
         let input = CompleteStr(
@@ -309,20 +541,20 @@ mod tests {
 [1518-11-05 00:55] wakes up");
 
         let parsed_events : Vec<Event> = events(input).unwrap().1;
-        let naps_per_guard: HashMap<GuardId, Vec<Nap>> = calc_naps(parsed_events);
+        let naps_per_guard: HashMap<GuardId, Vec<Nap>> = calc_naps(&parsed_events).unwrap();
 
-        let (guard_id, minute) = part2_impl(&naps_per_guard);
+        let (guard_id, minute) = part2_impl(&naps_per_guard).unwrap();
 
         assert_eq!(99 * 45, guard_id * minute as u16);
     }
 
     #[test]
     fn part2() {
-        let input = fs::read_to_string("./src/day04/input.txt").unwrap();
+        let input = input_or_skip!(4);
         let parsed_events = events(CompleteStr(&input)).unwrap().1;
-        let naps_per_guard: HashMap<GuardId, Vec<Nap>> = calc_naps(parsed_events);
+        let naps_per_guard: HashMap<GuardId, Vec<Nap>> = calc_naps(&parsed_events).unwrap();
 
-        let (guard_id, minute) = part2_impl(&naps_per_guard);
+        let (guard_id, minute) = part2_impl(&naps_per_guard).unwrap();
 
         assert_eq!(28198, guard_id * minute as u16);
     }
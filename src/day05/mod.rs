@@ -1,24 +1,37 @@
-enum ReactionType {
+use nom::{named, take_while};
+use nom::types::CompleteStr;
+
+use crate::backend::{Backend, Hand, Nom, Parser};
+use crate::error::{ErrorKind, ParseError, finish};
+use crate::solution::Solution;
+
+named!(
+    pub polymer<CompleteStr, CompleteStr>,
+    take_while!(|c: char| c.is_ascii_alphabetic())
+);
+
+pub enum ReactionType {
     Any,
     Single(char),
 }
 
-fn will_react_single_letter(c1: char, c2: char, reaction_type: &ReactionType) -> bool {
+pub fn will_react_single_letter(c1: char, c2: char, reaction_type: &ReactionType) -> bool {
     let reaction_type_match = match reaction_type {
         ReactionType::Any => true,
-        ReactionType::Single(c) => c1.eq_ignore_ascii_case(&c)
+        ReactionType::Single(c) => c1.eq_ignore_ascii_case(c)
     };
     c1.eq_ignore_ascii_case(&c2) && (c1 != c2) && reaction_type_match
 }
 
-fn will_react(c1: char, c2: char) -> bool {
+pub fn will_react(c1: char, c2: char) -> bool {
     will_react_single_letter(c1, c2, &ReactionType::Any)
 }
 
-fn react_single_letter(input: &str, reaction_type: &ReactionType) -> String {
-    if input.is_empty() { return String::new() }
-
+pub fn react_single_letter(input: &str, reaction_type: &ReactionType) -> String {
     let chars = input.chars().collect::<Vec<char>>();
+    // a single unit has nothing to react with
+    if chars.len() < 2 { return String::from(input) }
+
     let mut result = String::new();
 
     let mut i = 0;
@@ -27,7 +40,7 @@ fn react_single_letter(input: &str, reaction_type: &ReactionType) -> String {
         let c1 = chars[i];
         let c2 = chars[i+1];
 
-        if will_react_single_letter(c1, c2, &reaction_type) {
+        if will_react_single_letter(c1, c2, reaction_type) {
             i += 2;
         } else {
             result.push(c1);
@@ -42,11 +55,11 @@ fn react_single_letter(input: &str, reaction_type: &ReactionType) -> String {
     result
 }
 
-fn react(input: &str) -> String {
+pub fn react(input: &str) -> String {
     react_single_letter(input, &ReactionType::Any)
 }
 
-fn chain_react_single_letter(input: &str, reaction_type: ReactionType) -> String {
+pub fn chain_react_single_letter(input: &str, reaction_type: ReactionType) -> String {
     let result = react_single_letter(input, &reaction_type);
 
     if result.len() == input.len() {
@@ -58,16 +71,74 @@ fn chain_react_single_letter(input: &str, reaction_type: ReactionType) -> String
     }
 }
 
-fn chain_react(input: &str) -> String {
+pub fn chain_react(input: &str) -> String {
     chain_react_single_letter(input, ReactionType::Any)
 }
 
+/// Finds the shortest polymer which can be produced by removing all instances of
+/// a single unit type before reacting
+pub fn shortest_polymer_without_one_unit(input: &str) -> usize {
+    (b'a' ..= b'z')
+        .map(|c| {
+            let c = c as char;
+            let input_without_char = input
+                .replace([c, c.to_ascii_uppercase()], "");
+            chain_react(&input_without_char).len()
+        })
+        .min()
+        .unwrap()
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+
+    type Input = String;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        <Self as Parser<Backend>>::parse_input(input)
+    }
+
+    fn part1(polymer: &Self::Input) -> Option<Self::Part1> {
+        Some(chain_react(polymer).len())
+    }
+
+    fn part2(polymer: &Self::Input) -> Option<Self::Part2> {
+        Some(shortest_polymer_without_one_unit(polymer))
+    }
+}
+
+impl Parser<Nom> for Day05 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        // anything other than trailing whitespace after the letters is a bad unit
+        match polymer(CompleteStr(input)) {
+            Ok((rest, units)) if rest.trim_end().is_empty() => Ok(String::from(units.0)),
+            Ok((rest, _)) => Err(ParseError::at(input, input.len() - rest.len(), ErrorKind::Expected("letter"))),
+            Err(error) => finish(input, Err(error)),
+        }
+    }
+}
+
+impl Parser<Hand> for Day05 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        let polymer = input.trim_end();
+
+        match polymer.find(|c: char| !c.is_ascii_alphabetic()) {
+            Some(offset) => Err(ParseError::at(input, offset, ErrorKind::Expected("letter"))),
+            None => Ok(String::from(polymer)),
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod tests {
 
     use super::*;
-    use std::fs;
+    use crate::generate::{self, Rng};
 
     #[test]
     fn will_react_aA() {
@@ -119,6 +190,11 @@ mod tests {
         assert_eq!("", &react(""));
     }
 
+    #[test]
+    fn react_single_unit() {
+        assert_eq!("a", &react("a"));
+    }
+
     #[test]
     fn part1_example() {
         assert_eq!("dabCBAcaDA", &chain_react("dabAcCaCBAcCcaDA"));
@@ -129,34 +205,50 @@ mod tests {
         assert_eq!("oj", &chain_react("hHsSmMHhhHwWlLojYCclLy"));
     }
 
+    #[test]
+    fn parse_invalid_unit() {
+        let error = Day05::parse("aAb1B\n").unwrap_err();
+
+        assert_eq!(ErrorKind::Expected("letter"), error.kind);
+        assert_eq!((1, 4), (error.line, error.column));
+    }
+
     #[test]
     fn part1() {
-        let input = fs::read_to_string("./src/day05/input.txt").unwrap();
+        let input = input_or_skip!(5);
         assert_eq!(11814, chain_react(input.trim()).len())
     }
 
     #[test]
     fn part2() {
-        let input_raw = fs::read_to_string("./src/day05/input.txt").unwrap();
+        let input_raw = input_or_skip!(5);
         let input = input_raw.trim();
 
-        let best_reaction = (b'a' ..= b'z' )
-            .filter_map(|c| {
-                let c = c as char;
-                if c.is_alphabetic() { Some(c) } else { None }
-            })
-            .map(|c| {
-                let input_without_char = input
-                    .replace(c, "")
-                    .replace(c.to_ascii_uppercase(), "");
-                let length = chain_react(&input_without_char).len();
-                (c, length)
-            })
-            .max_by(|a, b| {
-                b.1.cmp(&a.1)
-            })
-            .unwrap();
-
-        assert_eq!(4282, best_reaction.1);
+        assert_eq!(4282, shortest_polymer_without_one_unit(input));
+    }
+
+    /// Reacts a polymer with a stack, the simplest correct way to do it
+    fn stack_react(polymer: &str) -> String {
+        let mut stack = vec![];
+        for unit in polymer.chars() {
+            match stack.last() {
+                Some(&top) if will_react(top, unit) => { stack.pop(); },
+                _ => stack.push(unit),
+            }
+        }
+        stack.into_iter().collect()
+    }
+
+    #[test]
+    fn chain_react_properties() {
+        for seed in 0..200 {
+            let polymer = generate::day05(&mut Rng::new(seed), seed as usize * 5);
+            let reacted = chain_react(&polymer);
+
+            let chars = reacted.chars().collect::<Vec<char>>();
+            assert!(chars.windows(2).all(|pair| !will_react(pair[0], pair[1])), "seed {}: {}", seed, reacted);
+            assert_eq!(reacted, chain_react(&reacted), "seed {}", seed);
+            assert_eq!(stack_react(&polymer), reacted, "seed {}", seed);
+        }
     }
 }
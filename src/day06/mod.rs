@@ -1,55 +1,61 @@
-use nom::{named, do_parse, separated_list, call, error_position, eol, map_res, tag, recognize};
+use nom::{named, do_parse, call, tag};
 use nom::types::CompleteStr;
-use std::str::FromStr;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Position {
-    x: u16,
-    y: u16,
-}
+use crate::backend::{Backend, Hand, Nom, Parser, parse_item};
+use crate::error::{ParseError, finish};
+use crate::geometry::{Manhattan, Metric, Point};
+use crate::grid::{Grid, SizeError};
+use crate::parsers::{lines, signed};
+use crate::scanner::{Scanner, complete};
+use crate::solution::{Solution, Unsolved};
 
-named!(
-    take_u16<CompleteStr, u16>,
-    map_res!(recognize!(nom::digit), |CompleteStr(s)| u16::from_str(s))
-);
+pub type Position = Point<i32>;
 
 named!(
-    position<CompleteStr, Position>,
+    pub position<CompleteStr, Position>,
     do_parse!(
-        x: take_u16 >>
+        x: call!(signed::<i32>) >>
         tag!(", ") >>
-        y: take_u16 >>
+        y: call!(signed::<i32>) >>
         (Position { x, y })
     )
 );
 
 named!(
-    positions<CompleteStr, Vec<Position> >,
-    separated_list!(eol, position)
+    pub positions<CompleteStr, Vec<Position> >,
+    call!(lines, position)
 );
 
-impl Position {
-    fn distance_from(&self, other: Self) -> u32 {
-        ( (self.x as i32 - other.x as i32).abs() +
-            (self.y as i32 - other.y as i32).abs() ) as u32
+fn scan_position(scanner: &mut Scanner) -> Result<Position, ParseError> {
+    let x = scanner.signed()?;
+    scanner.tag(", ")?;
+    let y = scanner.signed()?;
+
+    Ok(Position { x, y })
+}
+
+impl FromStr for Position {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_item(s, position, scan_position)
     }
 }
 
 enum MinDistance {
-    Unique(Position, u32),
-    NonUnique(u32),
+    Unique(Position, u64),
+    NonUnique(u64),
     None
 }
 
-type Grid = Vec<Vec<Option<Position>>>;
-
-fn closest(target: Position, positions: &Vec<Position>) -> Option<Position> {
+pub fn closest<M: Metric>(target: Position, positions: &[Position]) -> Option<Position> {
     let distances_per_point = positions.iter()
         .map(|&p| {
-            (p, p.distance_from(target))
-        }).collect::<Vec<(Position, u32)>>();
+            (p, p.distance::<M>(target))
+        }).collect::<Vec<(Position, u64)>>();
 
     let mut min_distance_tracker = MinDistance::None;
 
@@ -77,71 +83,44 @@ fn closest(target: Position, positions: &Vec<Position>) -> Option<Position> {
     }
 }
 
-fn create_grid(positions: &Vec<Position>) -> Grid {
-    let max_position = positions.iter()
-        .fold(Position { x: 0, y: 0}, |acc, &pos| {
-            Position {
-                x: acc.x.max(pos.x),
-                y: acc.y.max(pos.y),
-            }
-        });
-
-    let mut grid = vec![];
-    for y in 0..=max_position.y {
-
-        grid.push(vec![]);
-
-        for x in 0..=max_position.x {
-            let target = Position { x, y };
-            grid[y as usize].push(closest(target, &positions));
-        }
-    }
-
-    grid
+/// Maps every location between the positions to the position closest to it under
+/// the given metric, if there is a unique closest position
+///
+/// The grid only spans the positions, so positions far from the origin, or on
+/// either side of it, don't need a grid reaching back to it.
+pub fn create_grid<M: Metric>(positions: &[Position]) -> Result<Grid<Option<Position>>, SizeError> {
+    let left = positions.iter().map(|p| i64::from(p.x)).min().unwrap_or(0);
+    let top = positions.iter().map(|p| i64::from(p.y)).min().unwrap_or(0);
+    let right = positions.iter().map(|p| i64::from(p.x)).max().unwrap_or(0);
+    let bottom = positions.iter().map(|p| i64::from(p.y)).max().unwrap_or(0);
+
+    Grid::from_fn((left, top), (right - left) as usize + 1, (bottom - top) as usize + 1, |(x, y)| {
+        closest::<M>(Position::new(x as i32, y as i32), positions)
+    })
 }
 
-fn find_edge_points(grid: &Grid) -> HashSet<Position> {
-    let mut positions = HashSet::new();
-
-    for row_index in 0..grid.len() {
-        let row = &grid[row_index];
-
-        // the first and last rows are entirely edge
-        if row_index == 0 || row_index == grid.len() - 1 {
-            // push every point
-            for column_index in 0..row.len() {
-                if let Some(p) = &grid[row_index][column_index] {
-                    positions.insert(*p);
-                }
-            }
-        } else {
-            // other rows only push first and last element
-            for column_index in vec![0usize, row.len() - 1] {
-                if let Some(p) = &grid[row_index][column_index] {
-                    positions.insert(*p);
-                }
-            }
-        }
-    }
-
-    positions
+/// Positions with an area touching the edge of the grid, which will be infinite
+pub fn find_edge_points(grid: &Grid<Option<Position>>) -> HashSet<Position> {
+    grid.border()
+        .filter_map(|coord| grid[coord])
+        .collect()
 }
 
-fn count_point_instances_from(grid: &Grid) -> HashMap<Position, u32> {
-    grid.iter()
-        .flat_map(|row | { row })
-        .fold(HashMap::new(), |mut acc, position| {
-            if let Some(p) = position {
-                let count = acc.entry(*p).or_insert(0);
-                *count += 1;
-            }
+pub fn count_point_instances_from(grid: &Grid<Option<Position>>) -> HashMap<Position, u32> {
+    grid.cells()
+        .flatten()
+        .fold(HashMap::new(), |mut acc, p| {
+            let count = acc.entry(*p).or_insert(0);
+            *count += 1;
 
             acc
         })
 }
 
-fn part1_solve(positions: &Vec<Position>) -> u32 {
-    let grid = create_grid(positions);
+/// Size of the largest finite area closest to a single position under the given
+/// metric, or `None` if every area is infinite or the grid is too large
+pub fn largest_finite_area<M: Metric>(positions: &[Position]) -> Option<u32> {
+    let grid = create_grid::<M>(positions).ok()?;
     let counts = count_point_instances_from(&grid);
     let edge_points = find_edge_points(&grid);
 
@@ -149,15 +128,56 @@ fn part1_solve(positions: &Vec<Position>) -> u32 {
         .filter(|(position, _count)| {
             !edge_points.contains(position)
         })
-        .map(|(position, count)| *count)
+        .map(|(_position, count)| *count)
         .max()
-        .unwrap()
+}
+
+pub fn part1_solve(positions: &[Position]) -> Option<u32> {
+    largest_finite_area::<Manhattan>(positions)
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+
+    type Input = Vec<Position>;
+    type Part1 = u32;
+    type Part2 = Unsolved;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        <Self as Parser<Backend>>::parse_input(input)
+    }
+
+    fn part1(positions: &Self::Input) -> Option<Self::Part1> {
+        if positions.is_empty() { return None }
+
+        part1_solve(positions)
+    }
+
+    fn part2(_positions: &Self::Input) -> Option<Self::Part2> {
+        None
+    }
+}
+
+impl Parser<Nom> for Day06 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        finish(input, positions(CompleteStr(input)))
+    }
+}
+
+impl Parser<Hand> for Day06 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        complete(input, |scanner| scanner.lines(scan_position))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::error::ErrorKind;
+    use crate::generate;
+    use crate::geometry::Chebyshev;
 
     #[test]
     fn parse_positions() {
@@ -177,6 +197,24 @@ mod tests {
         assert_eq!(expected, positions(input).unwrap().1);
     }
 
+    #[test]
+    fn parse_serialize_round_trip() {
+        generate::assert_round_trip::<Day06>(0..20, generate::day06, |_| 30, |positions| generate::lines(positions));
+    }
+
+    #[test]
+    fn position_from_str() {
+        assert_eq!(Ok(Position::new(-3, 4)), "-3, 4".parse());
+        assert_eq!(ErrorKind::Expected("literal text"), "-3 4".parse::<Position>().unwrap_err().kind);
+    }
+
+    #[test]
+    fn serialize_canonical() {
+        let positions = Day06::parse("+1, -02\n3, 4\n").unwrap();
+
+        assert_eq!(vec!["1, -2", "3, 4"], positions.iter().map(Position::to_string).collect::<Vec<String>>());
+    }
+
     #[test]
     fn distance_from_itself() {
         let p1 = Position {
@@ -187,7 +225,7 @@ mod tests {
             x: 1, y: 2
         };
 
-        assert_eq!(0, p1.distance_from(p2));
+        assert_eq!(0, p1.distance::<Manhattan>(p2));
     }
 
     #[test]
@@ -200,7 +238,7 @@ mod tests {
             x: 1, y: 1
         };
 
-        assert_eq!(2, p1.distance_from(p2));
+        assert_eq!(2, p1.distance::<Manhattan>(p2));
     }
 
     #[test]
@@ -219,7 +257,7 @@ mod tests {
             x: 5, y: 6
         };
 
-        assert_eq!(Some(p2), closest(target, &positions));
+        assert_eq!(Some(p2), closest::<Manhattan>(target, &positions));
     }
 
     #[test]
@@ -234,13 +272,13 @@ mod tests {
 
         let positions = vec![p1, p2];
 
-        let grid = create_grid(&positions);
+        let grid = create_grid::<Manhattan>(&positions).unwrap();
 
         assert_eq!(
-            vec![
+            Grid::from_rows(vec![
                 vec![Some(p1), None],
                 vec![None, Some(p2)]
-            ],
+            ]).unwrap(),
             grid
         );
     }
@@ -257,14 +295,14 @@ mod tests {
 
         let positions = vec![p1, p2];
 
-        let grid = create_grid(&positions);
+        let grid = create_grid::<Manhattan>(&positions).unwrap();
 
         assert_eq!(
-            vec![
+            Grid::from_rows(vec![
                 vec![Some(p1), Some(p1), None],
                 vec![Some(p1), None, Some(p2)],
                 vec![None, Some(p2), Some(p2)],
-            ],
+            ]).unwrap(),
             grid
         );
     }
@@ -285,18 +323,71 @@ mod tests {
 
         let positions = vec![p1, p2, p3];
 
-        let grid = create_grid(&positions);
+        let grid = create_grid::<Manhattan>(&positions).unwrap();
 
         assert_eq!(
-            vec![
+            Grid::from_rows(vec![
                 vec![Some(p1), None, None],
                 vec![None, Some(p3), None],
                 vec![None, None, Some(p2)],
-            ],
+            ]).unwrap(),
             grid
         );
     }
 
+    #[test]
+    fn create_grid_by_metric() {
+        let p1 = Position::new(0, 0);
+        let p2 = Position::new(3, 1);
+        let positions = vec![p1, p2];
+
+        assert_eq!(
+            Grid::from_rows(vec![
+                vec![Some(p1), Some(p1), None, Some(p2)],
+                vec![Some(p1), None, Some(p2), Some(p2)],
+            ]).unwrap(),
+            create_grid::<Manhattan>(&positions).unwrap()
+        );
+        assert_eq!(
+            Grid::from_rows(vec![
+                vec![Some(p1), Some(p1), Some(p2), Some(p2)],
+                vec![Some(p1), Some(p1), Some(p2), Some(p2)],
+            ]).unwrap(),
+            create_grid::<Chebyshev>(&positions).unwrap()
+        );
+    }
+
+    #[test]
+    fn create_grid_negative_positions() {
+        let p1 = Position::new(-1, 0);
+        let p2 = Position::new(1, 0);
+
+        let grid = create_grid::<Manhattan>(&[p1, p2]).unwrap();
+
+        assert_eq!((-1, 0), grid.origin());
+        assert_eq!(vec![Some(p1), None, Some(p2)], grid.cells().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn create_grid_far_from_origin() {
+        let p1 = Position::new(100_000, 100_000);
+        let p2 = Position::new(100_005, 100_005);
+
+        let grid = create_grid::<Manhattan>(&[p1, p2]).unwrap();
+
+        assert_eq!((100_000, 100_000), grid.origin());
+        assert_eq!((6, 6), (grid.width(), grid.height()));
+        assert_eq!(None, Day06::part1(&vec![p1, p2]));
+    }
+
+    #[test]
+    fn create_grid_too_large() {
+        let positions = Day06::parse("2000000000, 0\n-2000000000, 0").unwrap();
+
+        assert_eq!(Err(SizeError { width: 4_000_000_001, height: 1 }), create_grid::<Manhattan>(&positions));
+        assert_eq!(None, Day06::part1(&positions));
+    }
+
     #[test]
     fn find_edge_points_five() {
         let p1 = Position {
@@ -319,11 +410,11 @@ mod tests {
             x: 2, y: 1
         };
 
-        let grid = vec![
+        let grid = Grid::from_rows(vec![
             vec![Some(p1), None, Some(p4)],
             vec![None, Some(p3), Some(p5)],
             vec![None, None, Some(p2)],
-        ];
+        ]).unwrap();
 
         let mut expected = HashSet::new();
         expected.insert(p1);
@@ -339,21 +430,28 @@ mod tests {
         let input = CompleteStr("1, 1\n1, 6\n8, 3\n3, 4\n5, 5\n8, 9");
         let positions = positions(input).unwrap().1;
 
-        let grid = create_grid(&positions);
+        let grid = create_grid::<Manhattan>(&positions).unwrap();
 
         let counts = count_point_instances_from(&grid);
 
         assert_eq!(17, *counts.get(&Position {x: 5, y: 5}).unwrap());
 
-        assert_eq!(17, part1_solve(&positions));
+        assert_eq!(Some(17), part1_solve(&positions));
+    }
+
+    #[test]
+    fn part1_all_areas_infinite() {
+        let positions = Day06::parse("1, 1\n5, 5").unwrap();
+
+        assert_eq!(None, Day06::part1(&positions));
     }
 
     #[test]
     fn part1() {
-        let input = fs::read_to_string("./src/day06/input.txt").unwrap();
+        let input = input_or_skip!(6);
         let positions = positions(CompleteStr(&input)).unwrap().1;
 
-        assert_eq!(5532, part1_solve(&positions));
+        assert_eq!(Some(5532), part1_solve(&positions));
     }
 
 }
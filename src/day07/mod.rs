@@ -1,12 +1,34 @@
-use nom::{named, do_parse, separated_list, call, error_position, eol, map_res, tag, recognize, take, parse_to};
+use nom::{named, do_parse, call, map_res, tag, take};
 use nom::types::CompleteStr;
 use std::str::FromStr;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fmt;
 
-type Step = char;
-type Prerequisite = char;
-type StepPrerequisites = Vec<Prerequisite>;
+use crate::backend::{Backend, Hand, Nom, Parser, parse_item};
+use crate::error::{ParseError, finish};
+use crate::parsers::lines;
+use crate::scanner::{Scanner, complete};
+use crate::solution::{Solution, Unsolved};
+
+pub type Step = char;
+pub type Prerequisite = char;
+pub type StepPrerequisites = Vec<Prerequisite>;
+
+/// One line of the puzzle input: a step which can't begin until its prerequisite
+/// is finished
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dependency {
+    pub step: Step,
+    pub prerequisite: Prerequisite,
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Step {} must be finished before step {} can begin.", self.prerequisite, self.step)
+    }
+}
 
 named!(
     take_char<CompleteStr, char>,
@@ -14,30 +36,49 @@ named!(
 );
 
 named!(
-    prereq<CompleteStr, (Step, Prerequisite)>,
+    pub prereq<CompleteStr, Dependency>,
     do_parse!(
         tag!("Step ") >>
         prereq: take_char >>
         tag!(" must be finished before step ") >>
         step: take_char >>
         tag!(" can begin.") >>
-        ( (step, prereq) )
+        ( Dependency { step, prerequisite: prereq } )
     )
 );
 
 named!(
-    prereqs<CompleteStr, Vec<(Step, Prerequisite)> >,
-    separated_list!(eol, prereq)
+    pub prereqs<CompleteStr, Vec<Dependency> >,
+    call!(lines, prereq)
 );
 
-struct Steps {
+fn scan_prereq(scanner: &mut Scanner) -> Result<Dependency, ParseError> {
+    scanner.tag("Step ")?;
+    let prereq = scanner.char()?;
+    scanner.tag(" must be finished before step ")?;
+    let step = scanner.char()?;
+    scanner.tag(" can begin.")?;
+
+    Ok(Dependency { step, prerequisite: prereq })
+}
+
+impl FromStr for Dependency {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_item(s, prereq, scan_prereq)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Steps {
     completed: HashSet<Step>,
     prerequisites: HashMap<Step, StepPrerequisites>,
 }
 
 impl Steps {
 
-    fn new(prerequisites: HashMap<Step, StepPrerequisites>) -> Self {
+    pub fn new(prerequisites: HashMap<Step, StepPrerequisites>) -> Self {
         Steps {
             completed: HashSet::new(),
             prerequisites,
@@ -46,7 +87,7 @@ impl Steps {
 
     /// Performs a step and returns the step which was performed
     /// If no steps are possible, or all steps are complete, returns None
-    fn perform_step(&mut self) -> Option<Step> {
+    pub fn perform_step(&mut self) -> Option<Step> {
         let non_completed_steps = self.prerequisites
             .iter()
             .filter(|(step, _prereqs)| {
@@ -68,22 +109,30 @@ impl Steps {
     }
 }
 
-fn generate_prereq_map(prereqs: &Vec<(Step, Prerequisite)>) -> HashMap<Step, StepPrerequisites> {
+pub fn generate_prereq_map(prereqs: &[Dependency]) -> HashMap<Step, StepPrerequisites> {
     let mut prereq_map = HashMap::new();
 
-    for (step, prereq) in prereqs {
-        let prereqs = prereq_map.entry(*step).or_insert(vec![]);
-        prereqs.push(*prereq);
+    for &Dependency { step, prerequisite: prereq } in prereqs {
+        let prereqs = prereq_map.entry(step).or_insert(vec![]);
+        prereqs.push(prereq);
 
-        if !prereq_map.contains_key(prereq) { prereq_map.insert(*prereq, vec![]); }
+        prereq_map.entry(prereq).or_insert(vec![]);
     }
 
     prereq_map
 }
 
-fn part1_impl(input: &str) -> String {
+pub fn part1_impl(input: &str) -> Option<String> {
     let prereqs = prereqs(CompleteStr(input)).unwrap().1;
-    let prereq_map = generate_prereq_map(&prereqs);
+    step_order(&prereqs)
+}
+
+/// Performs every step, returning the order they were completed in
+///
+/// Returns None if the steps can't all be performed because some depend on each other.
+pub fn step_order(prereqs: &[Dependency]) -> Option<String> {
+    let prereq_map = generate_prereq_map(prereqs);
+    let step_count = prereq_map.len();
 
     let mut steps = Steps::new(prereq_map);
     let mut step_tracker = vec![];
@@ -92,27 +141,64 @@ fn part1_impl(input: &str) -> String {
         step_tracker.push(completed_step);
     }
 
-    step_tracker.iter().collect()
+    if step_tracker.len() < step_count {
+        return None;
+    }
+    Some(step_tracker.iter().collect())
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+
+    type Input = Vec<Dependency>;
+    type Part1 = String;
+    type Part2 = Unsolved;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        <Self as Parser<Backend>>::parse_input(input)
+    }
+
+    fn part1(prereqs: &Self::Input) -> Option<Self::Part1> {
+        step_order(prereqs)
+    }
+
+    fn part2(_prereqs: &Self::Input) -> Option<Self::Part2> {
+        None
+    }
+}
+
+impl Parser<Nom> for Day07 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        finish(input, prereqs(CompleteStr(input)))
+    }
+}
+
+impl Parser<Hand> for Day07 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        complete(input, |scanner| scanner.lines(scan_prereq))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::generate::{self, Rng};
 
     #[test]
     fn parse_prereq() {
         let prereq = prereq(CompleteStr("Step C must be finished before step A can begin."))
             .unwrap().1;
 
-        assert_eq!('A', prereq.0);
-        assert_eq!('C', prereq.1);
+        assert_eq!('A', prereq.step);
+        assert_eq!('C', prereq.prerequisite);
     }
 
     #[test]
     fn parse_prereqs() {
-        let input = fs::read_to_string("./src/day07/input-example.txt").unwrap();
-        let prereqs = prereqs(CompleteStr(&input)).unwrap().1;
+        let input = include_str!("input-example.txt");
+        let prereqs = prereqs(CompleteStr(input)).unwrap().1;
 
         assert_eq!(7, prereqs.len());
 
@@ -122,6 +208,20 @@ mod tests {
         assert_eq!(Some(&vec![]), prereq_map.get(&'C'));
     }
 
+    #[test]
+    fn parse_serialize_round_trip() {
+        generate::assert_round_trip::<Day07>(0..20, generate::day07, |seed| 2 + seed as usize, |prereqs| generate::lines(prereqs));
+    }
+
+    #[test]
+    fn dependency_from_str() {
+        assert_eq!(
+            Ok(Dependency { step: 'A', prerequisite: 'C' }),
+            "Step C must be finished before step A can begin.".parse()
+        );
+        assert!("Step C must be finished before step A".parse::<Dependency>().is_err());
+    }
+
     #[test]
     fn perform_step_no_steps() {
         let mut steps = Steps::new(HashMap::new());
@@ -130,14 +230,37 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        let input = fs::read_to_string("./src/day07/input-example.txt").unwrap();
-        assert_eq!(String::from("CABDFE"), part1_impl(&input));
+        let input = include_str!("input-example.txt");
+        assert_eq!(Some(String::from("CABDFE")), part1_impl(input));
+    }
+
+    #[test]
+    fn part1_cyclic_steps() {
+        assert_eq!(None, part1_impl("Step A must be finished before step A can begin."));
+        assert_eq!(None, part1_impl("Step A must be finished before step B can begin.\nStep B must be finished before step A can begin."));
+        assert_eq!(None, part1_impl("Step C must be finished before step A can begin.\nStep A must be finished before step B can begin.\nStep B must be finished before step A can begin."));
     }
 
     #[test]
     fn part1() {
-        let input = fs::read_to_string("./src/day07/input.txt").unwrap();
-        assert_eq!(String::from("BHMOTUFLCPQKWINZVRXAJDSYEG"), part1_impl(&input));
+        let input = input_or_skip!(7);
+        assert_eq!(Some(String::from("BHMOTUFLCPQKWINZVRXAJDSYEG")), part1_impl(&input));
     }
 
+    #[test]
+    fn part1_is_topological_order() {
+        for seed in 0..100 {
+            let input = generate::day07(&mut Rng::new(seed), 2 + seed as usize % 25);
+            let order = part1_impl(&input).unwrap();
+            let position = |step: char| order.find(step).unwrap_or_else(|| panic!("seed {}: {} missing from {}", seed, step, order));
+
+            let prereqs = Day07::parse(&input).unwrap();
+            let steps = prereqs.iter().flat_map(|dependency| vec![dependency.step, dependency.prerequisite]).collect::<HashSet<char>>();
+
+            assert_eq!(steps.len(), order.len(), "seed {}: {}", seed, order);
+            for Dependency { step, prerequisite: prereq } in prereqs {
+                assert!(position(prereq) < position(step), "seed {}: {} must come before {} in {}", seed, prereq, step, order);
+            }
+        }
+    }
 }
@@ -1,47 +1,136 @@
-use nom::{named, do_parse, separated_list, call, error_position, eol, map_res, tag, recognize, take, parse_to, count, ws, sep, wrap_sep};
+use nom::{named, do_parse, call, tag};
 use nom::types::CompleteStr;
+use std::fmt;
 use std::str::FromStr;
-use std::collections::HashMap;
-use std::collections::HashSet;
 
-type Metadata = Vec<u16>;
-type Nodes = Vec<Box<Node>>;
+use crate::backend::{Backend, Hand, Nom, Parser, parse_item};
+use crate::error::{ParseError, finish};
+use crate::parsers::{counted, spaced, unsigned};
+use crate::scanner::{Scanner, complete};
+use crate::solution::{Solution, Unsolved};
+
+pub type Metadata = Vec<u16>;
+pub type Nodes = Vec<Box<Node>>;
 
 #[derive(Debug, PartialEq)]
-struct Node {
-    metadata: Metadata,
-    children: Nodes,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Node {
+    pub metadata: Metadata,
+    pub children: Nodes,
 }
 
-named!(
-    take_u16<CompleteStr, u16>,
-    map_res!(recognize!(nom::digit), |CompleteStr(s)| u16::from_str(s))
-);
+/// Writes the node in the same form as the puzzle input
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.children.len(), self.metadata.len())?;
+        for child in &self.children {
+            write!(f, " {}", child)?;
+        }
+        for metadata in &self.metadata {
+            write!(f, " {}", metadata)?;
+        }
+
+        Ok(())
+    }
+}
 
 named!(
-    node<CompleteStr, Box<Node> >,
+    pub node<CompleteStr, Box<Node> >,
     do_parse!(
-        num_children: take_u16 >>
+        num_children: call!(unsigned::<u16>) >>
         tag!(" ") >>
-        num_metadata: take_u16 >>
-        children: count!(ws!(node), num_children as usize) >>
-        metadata: count!(ws!(take_u16), num_metadata as usize) >>
+        num_metadata: call!(unsigned::<u16>) >>
+        children: call!(counted, spaced_node, num_children as usize) >>
+        metadata: call!(counted, spaced_u16, num_metadata as usize) >>
         (Box::new(Node { metadata, children }))
     )
 );
 
-fn sum_metadata(node: &Node) -> u64 {
+named!(
+    spaced_node<CompleteStr, Box<Node> >,
+    call!(spaced, node)
+);
+
+named!(
+    spaced_u16<CompleteStr, u16>,
+    call!(spaced, unsigned::<u16>)
+);
+
+fn scan_node(scanner: &mut Scanner) -> Result<Node, ParseError> {
+    let num_children = scanner.unsigned::<u16>()?;
+    scanner.tag(" ")?;
+    let num_metadata = scanner.unsigned::<u16>()?;
+
+    let mut children = Vec::with_capacity(num_children as usize);
+    for _ in 0..num_children {
+        scanner.skip_whitespace();
+        children.push(Box::new(scan_node(scanner)?));
+    }
+
+    let mut metadata = Vec::with_capacity(num_metadata as usize);
+    for _ in 0..num_metadata {
+        scanner.skip_whitespace();
+        metadata.push(scanner.unsigned()?);
+    }
+
+    Ok(Node { metadata, children })
+}
+
+impl FromStr for Node {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_item(s, |input| node(input).map(|(rest, node)| (rest, *node)), scan_node)
+    }
+}
+
+pub fn sum_metadata(node: &Node) -> u64 {
     node.metadata.iter().map(|metadata| *metadata as u64).sum::<u64>() +
         node.children.iter()
             .fold(0, |acc, node| {
-                acc + sum_metadata(&**node)
+                acc + sum_metadata(node)
             })
 }
 
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+
+    type Input = Node;
+    type Part1 = u64;
+    type Part2 = Unsolved;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        <Self as Parser<Backend>>::parse_input(input)
+    }
+
+    fn part1(node: &Self::Input) -> Option<Self::Part1> {
+        Some(sum_metadata(node))
+    }
+
+    fn part2(_node: &Self::Input) -> Option<Self::Part2> {
+        None
+    }
+}
+
+impl Parser<Nom> for Day08 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        finish(input, node(CompleteStr(input))).map(|node| *node)
+    }
+}
+
+impl Parser<Hand> for Day08 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        complete(input, scan_node)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::generate;
+    use crate::error::ErrorKind;
 
     #[test]
     fn parse_node_no_children() {
@@ -73,20 +162,58 @@ mod tests {
         assert_eq!(101, sum_metadata(&node));
     }
 
+    #[test]
+    fn parse_nodes_missing_metadata() {
+        let error = Day08::parse("1 1 0 1 99").unwrap_err();
+
+        assert_eq!(ErrorKind::Expected("digit"), error.kind);
+        assert_eq!((1, 11), (error.line, error.column));
+    }
+
+    #[test]
+    fn parse_nodes_trailing_input() {
+        let error = Day08::parse("0 1 5 7\n").unwrap_err();
+
+        assert_eq!(ErrorKind::TrailingInput, error.kind);
+        assert_eq!((1, 7), (error.line, error.column));
+    }
+
     #[test]
     fn parse_nodes_example() {
-        let input = fs::read_to_string("./src/day08/input-example.txt").unwrap();
-        let node = node(CompleteStr(&input)).unwrap().1;
+        let input = include_str!("input-example.txt");
+        let node = node(CompleteStr(input)).unwrap().1;
 
         assert_eq!(138, sum_metadata(&node));
     }
 
     #[test]
     fn part1() {
-        let input = fs::read_to_string("./src/day08/input.txt").unwrap();
+        let input = input_or_skip!(8);
         let node = node(CompleteStr(&input)).unwrap().1;
 
         assert_eq!(41454, sum_metadata(&node));
     }
 
+    #[test]
+    fn node_from_str() {
+        let node = "1 1 0 1 99 2".parse::<Node>().unwrap();
+
+        assert_eq!(vec![2], node.metadata);
+        assert_eq!(vec![99], node.children[0].metadata);
+        assert!("1 1 0 1 99".parse::<Node>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let node = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2".parse::<Node>().unwrap();
+        let json = serde_json::to_string(&node).unwrap();
+
+        assert_eq!(node, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn parse_serialize_round_trip() {
+        generate::assert_round_trip::<Day08>(0..100, generate::day08, |seed| 1 + seed as usize * 3, Node::to_string);
+    }
 }
@@ -1,14 +1,69 @@
-type MarbleValue = u64;
-type Score = u64;
+use nom::{named, do_parse, call, tag};
+use nom::types::CompleteStr;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 
-struct MarbleCircle {
+use crate::backend::{Backend, Hand, Nom, Parser, parse_item};
+use crate::error::{ParseError, finish};
+use crate::parsers::unsigned;
+use crate::scanner::{Scanner, complete};
+use crate::solution::{Solution, Unsolved};
+
+pub type MarbleValue = u64;
+pub type Score = u64;
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game {
+    pub num_players: usize,
+    pub max_marble_value: MarbleValue,
+}
+
+/// Writes the game in the same form as the puzzle input
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} players; last marble is worth {} points", self.num_players, self.max_marble_value)
+    }
+}
+
+named!(
+    pub game<CompleteStr, Game>,
+    do_parse!(
+        num_players: call!(unsigned::<usize>) >>
+        tag!(" players; last marble is worth ") >>
+        max_marble_value: call!(unsigned::<MarbleValue>) >>
+        tag!(" points") >>
+        (Game { num_players, max_marble_value })
+    )
+);
+
+fn scan_game(scanner: &mut Scanner) -> Result<Game, ParseError> {
+    let num_players = scanner.unsigned()?;
+    scanner.tag(" players; last marble is worth ")?;
+    let max_marble_value = scanner.unsigned()?;
+    scanner.tag(" points")?;
+
+    Ok(Game { num_players, max_marble_value })
+}
+
+impl FromStr for Game {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_item(s, game, scan_game)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarbleCircle {
     pub circle: Vec<MarbleValue>,
     pub active_marble_index: usize,
     pub last_marble_placed: MarbleValue,
 }
 
 impl MarbleCircle {
-    fn new() -> Self {
+    pub fn new() -> Self {
         MarbleCircle {
             circle: vec![0],
             active_marble_index: 0,
@@ -17,9 +72,9 @@ impl MarbleCircle {
     }
 
     /// returns the points scored on that turn
-    fn place_next_marble(&mut self) -> Score {
+    pub fn place_next_marble(&mut self) -> Score {
         let marble_to_be_placed = self.last_marble_placed + 1;
-        let score = if marble_to_be_placed % 23 == 0 {
+        let score = if marble_to_be_placed.is_multiple_of(23) {
             let removed_marble_index = {
                 let removed_marble_index = self.active_marble_index as i64 - 7;
                 if removed_marble_index < 0 { self.circle.len() as i64 + removed_marble_index }
@@ -51,11 +106,23 @@ impl MarbleCircle {
     }
 }
 
-fn part1_impl(num_players: usize, max_marble_value: u64) -> Score {
+impl Default for MarbleCircle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plays the game and returns the winning score
+///
+/// Players after the last one to place a marble never score, so only the players
+/// who get a turn are kept.
+pub fn part1_impl(num_players: usize, max_marble_value: u64) -> Score {
+    let players_with_turns = usize::try_from(max_marble_value).map_or(num_players, |turns| num_players.min(turns));
+
     let mut marble_circle = MarbleCircle::new();
-    let mut players = vec![0u64; num_players];
+    let mut players = vec![0u64; players_with_turns.max(1)];
     let mut active_player = 0usize;
-    for i in 0..max_marble_value {
+    for _ in 0..max_marble_value {
         players[active_player] += marble_circle.place_next_marble();
         active_player = (active_player + 1) % players.len();
     }
@@ -63,11 +130,51 @@ fn part1_impl(num_players: usize, max_marble_value: u64) -> Score {
     *players.iter().max().unwrap()
 }
 
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+
+    type Input = Game;
+    type Part1 = Score;
+    type Part2 = Unsolved;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        <Self as Parser<Backend>>::parse_input(input)
+    }
+
+    fn part1(game: &Self::Input) -> Option<Self::Part1> {
+        if game.num_players == 0 { return None }
+
+        Some(part1_impl(game.num_players, game.max_marble_value))
+    }
+
+    // part two is the same game with 100 times as many marbles, which is too slow
+    // while the circle is backed by a Vec
+    fn part2(_game: &Self::Input) -> Option<Self::Part2> {
+        None
+    }
+}
+
+
+impl Parser<Nom> for Day09 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        finish(input, game(CompleteStr(input)))
+    }
+}
+
+impl Parser<Hand> for Day09 {
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError> {
+        complete(input, scan_game)
+    }
+}
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::generate::{self, Rng};
+    use std::collections::VecDeque;
 
     #[test]
     fn marble_circle_new() {
@@ -103,7 +210,7 @@ mod tests {
     #[test]
     fn marble_circle_add_marble_23() {
         let mut marble_circle = MarbleCircle::new();
-        for i in 0..22 {
+        for _ in 0..22 {
             marble_circle.place_next_marble(); // place the first 22 marbles
         }
 
@@ -118,13 +225,81 @@ mod tests {
         assert_eq!(23, marble_circle.last_marble_placed);
     }
 
+    #[test]
+    fn parse_game() {
+        let expected = Game {
+            num_players: 9,
+            max_marble_value: 25,
+        };
+
+        assert_eq!(expected, game(CompleteStr("9 players; last marble is worth 25 points")).unwrap().1);
+    }
+
     #[test]
     fn part1_example() {
         assert_eq!(32u64, part1_impl(9, 25));
     }
 
+    #[test]
+    fn part1_more_players_than_marbles() {
+        assert_eq!(32u64, part1_impl(100_000_000_000, 25));
+
+        let game = Day09::parse("100000000000 players; last marble is worth 10 points").unwrap();
+        assert_eq!(Some(0), Day09::part1(&game));
+        assert_eq!(0u64, part1_impl(5, 0));
+    }
+
     #[test]
     fn part1() {
         assert_eq!(408679u64, part1_impl(424, 71482));
     }
+
+    /// Plays the game with the current marble always at the back of a rotating deque
+    fn naive_high_score(num_players: usize, max_marble_value: u64) -> Score {
+        let mut circle = VecDeque::new();
+        circle.push_back(0);
+        let mut scores = vec![0; num_players];
+
+        for marble in 1..=max_marble_value {
+            if marble % 23 == 0 {
+                circle.rotate_right(7);
+                scores[(marble as usize - 1) % num_players] += marble + circle.pop_back().unwrap();
+                circle.rotate_left(1);
+            } else {
+                circle.rotate_left(1);
+                circle.push_back(marble);
+            }
+        }
+
+        scores.into_iter().max().unwrap()
+    }
+
+    #[test]
+    fn game_from_str() {
+        assert_eq!(
+            Ok(Game { num_players: 9, max_marble_value: 25 }),
+            "9 players; last marble is worth 25 points".parse()
+        );
+        assert!("9 players".parse::<Game>().is_err());
+    }
+
+    #[test]
+    fn parse_serialize_round_trip() {
+        generate::assert_round_trip::<Day09>(0..20, generate::day09, |seed| seed as usize * 100, Game::to_string);
+    }
+
+    #[test]
+    fn part1_matches_naive_simulation() {
+        let mut rng = Rng::new(9);
+        for _ in 0..100 {
+            let num_players = rng.between(1, 30) as usize;
+            let max_marble_value = rng.between(0, 2000) as u64;
+
+            assert_eq!(
+                naive_high_score(num_players, max_marble_value),
+                part1_impl(num_players, max_marble_value),
+                "{} players, last marble {}", num_players, max_marble_value
+            );
+        }
+    }
 }
@@ -0,0 +1,240 @@
+//! A small subset of TOML used for the config and answers files
+//!
+//! Documents are made of `[table]` headers followed by `key = value` pairs, with `#`
+//! comments. Values are either double quoted strings or bare words such as numbers.
+//! Dotted keys are not nested, so `day01.part1` is simply a key containing a dot.
+
+use nom::{named, do_parse, alt, call, delimited, opt, tag, take_while, take_while1, value, preceded};
+use nom::types::CompleteStr;
+use nom::{Context, Err, ErrorKind, IResult};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::error::{ParseError, finish};
+use crate::parsers::lines;
+
+pub type Table = BTreeMap<String, String>;
+
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct Document {
+    /// Pairs which appear before any table header are stored under the empty name
+    tables: BTreeMap<String, Table>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Line {
+    Blank,
+    Table(String),
+    Pair(String, String),
+}
+
+fn is_bare_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+'
+}
+
+fn is_space(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+fn quoted(input: CompleteStr) -> IResult<CompleteStr, String> {
+    if !input.starts_with('"') {
+        return Err(Err::Error(Context::Code(input, ErrorKind::Tag)));
+    }
+
+    let mut value = String::new();
+    let mut chars = input.char_indices().skip(1);
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((CompleteStr(&input[i + 1..]), value)),
+            '\\' => match chars.next() {
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\\')) => value.push('\\'),
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                _ => return Err(Err::Failure(Context::Code(CompleteStr(&input[i..]), ErrorKind::Escaped))),
+            },
+            '\n' => break,
+            c => value.push(c),
+        }
+    }
+
+    Err(Err::Failure(Context::Code(input, ErrorKind::Tag)))
+}
+
+named!(
+    bare<CompleteStr, String>,
+    do_parse!(
+        word: take_while1!(is_bare_char) >>
+        (String::from(word.0))
+    )
+);
+
+named!(
+    word<CompleteStr, String>,
+    alt!(quoted | bare)
+);
+
+named!(
+    space<CompleteStr, CompleteStr>,
+    take_while!(is_space)
+);
+
+named!(
+    comment<CompleteStr, CompleteStr>,
+    preceded!(tag!("#"), take_while!(|c| c != '\n' && c != '\r'))
+);
+
+named!(
+    line<CompleteStr, Line>,
+    do_parse!(
+        space >>
+        line: alt!(
+            delimited!(tag!("["), word, tag!("]")) => { Line::Table } |
+            do_parse!(
+                key: word >>
+                space >>
+                tag!("=") >>
+                space >>
+                value: word >>
+                (Line::Pair(key, value))
+            ) |
+            value!(Line::Blank)
+        ) >>
+        space >>
+        opt!(comment) >>
+        (line)
+    )
+);
+
+named!(
+    document_lines<CompleteStr, Vec<Line> >,
+    call!(lines, line)
+);
+
+fn quote(s: &str) -> String {
+    let escaped = s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+
+    format!("\"{}\"", escaped)
+}
+
+fn key(s: &str) -> String {
+    if !s.is_empty() && s.chars().all(is_bare_char) { String::from(s) } else { quote(s) }
+}
+
+impl Document {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut document = Document::default();
+        let mut current_table = String::new();
+
+        for line in finish(input, document_lines(CompleteStr(input)))? {
+            match line {
+                Line::Blank => {},
+                Line::Table(name) => {
+                    document.tables.entry(name.clone()).or_default();
+                    current_table = name;
+                },
+                Line::Pair(key, value) => document.set(&current_table, &key, &value),
+            }
+        }
+
+        Ok(document)
+    }
+
+    pub fn get(&self, table: &str, key: &str) -> Option<&str> {
+        self.tables.get(table)
+            .and_then(|table| table.get(key))
+            .map(String::as_str)
+    }
+
+    pub fn set(&mut self, table: &str, key: &str, value: &str) {
+        self.tables.entry(String::from(table))
+            .or_default()
+            .insert(String::from(key), String::from(value));
+    }
+
+    pub fn table(&self, name: &str) -> Option<&Table> {
+        self.tables.get(name)
+    }
+
+    /// Iterates over named tables in order, skipping pairs which have no table
+    pub fn tables(&self) -> impl Iterator<Item = (&str, &Table)> {
+        self.tables.iter()
+            .filter(|(name, _)| !name.is_empty())
+            .map(|(name, table)| (name.as_str(), table))
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(root) = self.tables.get("") {
+            for (k, v) in root {
+                writeln!(f, "{} = {}", key(k), quote(v))?;
+            }
+        }
+
+        for (name, table) in self.tables() {
+            writeln!(f, "\n[{}]", key(name))?;
+            for (k, v) in table {
+                writeln!(f, "{} = {}", key(k), quote(v))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn parse_document() {
+        let input = "# answers\n\
+                     input_dir = \"../inputs\"\n\
+                     \n\
+                     [default]\n\
+                     day01.part1 = 538 # frequency\n\
+                     day07.part1 = \"BHMOTUFLCPQKWINZVRXAJDSYEG\"\n\
+                     \n\
+                     [\"team mate\"]\r\n\
+                     \"odd key\" = \"a \\\"quoted\\\" value\"\r\n";
+
+        let document = Document::parse(input).unwrap();
+
+        assert_eq!(Some("../inputs"), document.get("", "input_dir"));
+        assert_eq!(Some("538"), document.get("default", "day01.part1"));
+        assert_eq!(Some("BHMOTUFLCPQKWINZVRXAJDSYEG"), document.get("default", "day07.part1"));
+        assert_eq!(Some("a \"quoted\" value"), document.get("team mate", "odd key"));
+        assert_eq!(vec!["default", "team mate"], document.tables().map(|(name, _)| name).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn parse_error() {
+        let error = Document::parse("[default]\nday01.part1 538").unwrap_err();
+
+        assert_eq!(ErrorKind::Expected("end of line"), error.kind);
+        assert_eq!((2, 1), (error.line, error.column));
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let error = Document::parse("key = \"value\nother = 1").unwrap_err();
+
+        assert_eq!((1, 7), (error.line, error.column));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut document = Document::default();
+        document.set("", "input_dir", "inputs");
+        document.set("default", "day01.part1", "538");
+        document.set("team mate", "day02.part2", "line\nbreak \"quoted\"");
+
+        assert_eq!(document, Document::parse(&document.to_string()).unwrap());
+    }
+}
@@ -0,0 +1,144 @@
+use nom::types::CompleteStr;
+use nom::{Context, Err, IResult};
+use std::fmt;
+
+use crate::parsers::OVERFLOW;
+
+/// What went wrong at the position a `ParseError` points to
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ErrorKind {
+    /// The grammar expected a token which was not found
+    Expected(&'static str),
+    /// The grammar was satisfied but some input was not consumed
+    TrailingInput,
+    /// A number was too large, or too small, to fit in its type
+    Overflow,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::Expected(expected) => write!(f, "expected {}", expected),
+            ErrorKind::TrailingInput => write!(f, "unexpected trailing input"),
+            ErrorKind::Overflow => write!(f, "number out of range"),
+        }
+    }
+}
+
+/// Returned when puzzle input does not match the grammar for a day
+///
+/// Line and column are 1-based, and the column counts characters rather than bytes.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub kind: ErrorKind,
+    pub line: usize,
+    pub column: usize,
+    /// The full text of the line containing the error, used to render a snippet
+    pub line_text: String,
+}
+
+impl ParseError {
+    /// Creates an error pointing at the given byte offset into the input
+    pub fn at(input: &str, offset: usize, kind: ErrorKind) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = input[offset..].find('\n').map(|i| offset + i).unwrap_or(input.len());
+
+        ParseError {
+            kind,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            line_text: String::from(input[line_start..line_end].trim_end_matches('\r')),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+
+        writeln!(f, "{} at line {}, column {}", self.kind, self.line, self.column)?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.line_text)?;
+        write!(f, "{} | {}^", gutter, " ".repeat(self.column - 1))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn describe(kind: &nom::ErrorKind) -> &'static str {
+    match kind {
+        nom::ErrorKind::Tag => "literal text",
+        nom::ErrorKind::Digit => "digit",
+        nom::ErrorKind::Alpha => "letter",
+        nom::ErrorKind::AlphaNumeric => "letter or digit",
+        nom::ErrorKind::Space | nom::ErrorKind::MultiSpace => "whitespace",
+        nom::ErrorKind::CrLf => "end of line",
+        nom::ErrorKind::Eof => "end of input",
+        nom::ErrorKind::Alt => "one of several alternatives",
+        nom::ErrorKind::MapRes => "valid value",
+        _ => "valid input",
+    }
+}
+
+/// Converts the result of a top level nom parser into the crate error type
+///
+/// Anything other than whitespace left over after parsing is treated as an error, so
+/// a parser which stops early can't silently drop part of the input.
+pub(crate) fn finish<'a, T>(input: &'a str, result: IResult<CompleteStr<'a>, T>) -> Result<T, ParseError> {
+    let offset = |rest: CompleteStr| input.len() - rest.len();
+
+    match result {
+        Ok((rest, value)) => {
+            let trailing = rest.trim_start();
+            if trailing.is_empty() {
+                Ok(value)
+            } else {
+                Err(ParseError::at(input, input.len() - trailing.len(), ErrorKind::TrailingInput))
+            }
+        },
+        Err(Err::Error(Context::Code(rest, kind))) | Err(Err::Failure(Context::Code(rest, kind))) => {
+            let kind = match kind {
+                nom::ErrorKind::Custom(OVERFLOW) => ErrorKind::Overflow,
+                kind => ErrorKind::Expected(describe(&kind)),
+            };
+            Err(ParseError::at(input, offset(rest), kind))
+        },
+        Err(Err::Incomplete(_)) => {
+            Err(ParseError::at(input, input.len(), ErrorKind::Expected("more input")))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_of_offset() {
+        let error = ParseError::at("+1\n+23x\n-4", 6, ErrorKind::TrailingInput);
+
+        assert_eq!(2, error.line);
+        assert_eq!(4, error.column);
+        assert_eq!("+23x", error.line_text);
+    }
+
+    #[test]
+    fn position_at_end_of_input() {
+        let error = ParseError::at("+1\n", 3, ErrorKind::Expected("digit"));
+
+        assert_eq!(2, error.line);
+        assert_eq!(1, error.column);
+        assert_eq!("", error.line_text);
+    }
+
+    #[test]
+    fn render_snippet() {
+        let error = ParseError::at("+1\n+23x\n-4", 6, ErrorKind::TrailingInput);
+
+        assert_eq!(
+            "unexpected trailing input at line 2, column 4\n  |\n2 | +23x\n  |    ^",
+            error.to_string()
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Downloads puzzle inputs into the input dir
+
+use crate::client::{Client, ClientError};
+use crate::input::{DEFAULT, Inputs};
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum FetchError {
+    Client(ClientError),
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::Client(e) => write!(f, "{}", e),
+            FetchError::Io(path, e) => write!(f, "failed to write {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<ClientError> for FetchError {
+    fn from(e: ClientError) -> Self {
+        FetchError::Client(e)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Fetched {
+    /// The input was already in the input dir, so no request was made
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+/// Downloads the input for a day unless it is already cached as the default input
+pub fn fetch_input(client: &Client, inputs: &Inputs, day: u8) -> Result<Fetched, FetchError> {
+    let path = inputs.path(day, DEFAULT);
+    if path.exists() {
+        return Ok(Fetched::Cached(path));
+    }
+
+    let input = client.input(day)?;
+
+    let day_dir = inputs.day_dir(day);
+    fs::create_dir_all(&day_dir).map_err(|e| FetchError::Io(day_dir, e))?;
+    fs::write(&path, input).map_err(|e| FetchError::Io(path.clone(), e))?;
+
+    Ok(Fetched::Downloaded(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stub_server::StubServer;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn fetch_once() {
+        let dir = env::temp_dir().join(format!("aoc-2018-fetch-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let inputs = Inputs::new(&dir);
+        let server = StubServer::start(vec![(200, String::from("1 1 0 1 99 2\n"))]);
+        let client = Client::new(&server.url(), "abc123");
+
+        let path = dir.join("day08/input.txt");
+        assert_eq!(Fetched::Downloaded(path.clone()), fetch_input(&client, &inputs, 8).unwrap());
+        assert_eq!(Fetched::Cached(path), fetch_input(&client, &inputs, 8).unwrap());
+
+        assert_eq!(1, server.requests().len());
+        assert_eq!("1 1 0 1 99 2", inputs.load(8, DEFAULT).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_fetch_is_not_cached() {
+        let dir = env::temp_dir().join(format!("aoc-2018-fetch-fail-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let inputs = Inputs::new(&dir);
+        let server = StubServer::start(vec![(400, String::from("Puzzle inputs differ by user.  Please log in to get your puzzle input."))]);
+        let client = Client::new(&server.url(), "expired");
+
+        assert!(fetch_input(&client, &inputs, 3).is_err());
+        assert!(!inputs.path(3, DEFAULT).exists());
+    }
+}
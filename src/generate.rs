@@ -0,0 +1,493 @@
+//! Seeded random puzzle inputs for every day, at any scale
+//!
+//! Each generator produces input in the same format as the real puzzle, and keeps
+//! the guarantees the puzzles rely on, such as day02 having exactly one pair of IDs
+//! one letter apart, so the solvers can be stress tested well beyond the size of the
+//! shipped inputs. The same seed and scale always produce the same input.
+
+use crate::grid::Grid;
+
+use std::collections::{BTreeSet, HashSet};
+
+/// A small, fast generator (SplitMix64) whose output never changes between
+/// versions, so seeds stay reproducible
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, which must not be empty
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "empty range");
+        ((u128::from(self.next_u64()) * u128::from(n)) >> 64) as u64
+    }
+
+    /// A number in `low..=high`
+    pub fn between(&mut self, low: i64, high: i64) -> i64 {
+        let span = (high - low) as u64 + 1;
+        low + self.below(span) as i64
+    }
+
+    /// True with probability `numerator / denominator`
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Generates an input for a day, or `None` if there is no generator for it
+pub fn generate(day: u8, seed: u64, scale: usize) -> Option<String> {
+    let rng = &mut Rng::new(seed);
+
+    let input = match day {
+        1 => day01(rng, scale),
+        2 => day02(rng, scale),
+        3 => day03(rng, scale),
+        4 => day04(rng, scale),
+        5 => day05(rng, scale),
+        6 => day06(rng, scale),
+        7 => day07(rng, scale),
+        8 => day08(rng, scale),
+        9 => day09(rng, scale),
+        _ => return None,
+    };
+
+    Some(input)
+}
+
+/// At least two frequency changes
+///
+/// The drift over one pass of the list is smaller than the number of changes, so by
+/// the pigeonhole principle two running totals are congruent modulo the drift, and
+/// some frequency is always repeated.
+pub fn day01(rng: &mut Rng, scale: usize) -> String {
+    let count = scale.max(2);
+    let max_drift = count as i64 - 1;
+
+    let mut changes = (1..count).map(|_| rng.between(-100, 100)).collect::<Vec<i64>>();
+    let drift = rng.between(-max_drift, max_drift);
+    changes.push(drift - changes.iter().sum::<i64>());
+
+    let lines = changes.iter()
+        .map(|change| format!("{:+}", change))
+        .collect::<Vec<String>>();
+    lines.join("\n")
+}
+
+/// At least two box IDs of 26 lowercase letters, of which exactly one pair differs
+/// by a single letter
+///
+/// Each ID holds its index twice in letters at shuffled positions, so IDs with
+/// different indices always differ in at least two places. The near match is a copy
+/// of one ID with a letter changed outside those positions.
+pub fn day02(rng: &mut Rng, scale: usize) -> String {
+    const LENGTH: usize = 26;
+
+    let count = scale.max(2);
+    let index_digits = (1..).find(|&digits| 26f64.powi(digits as i32) >= count as f64).unwrap();
+
+    let mut positions = (0..LENGTH).collect::<Vec<usize>>();
+    rng.shuffle(&mut positions);
+    let (index_positions, free_positions) = positions.split_at(index_digits * 2);
+
+    let mut ids = (0..count - 1)
+        .map(|index| {
+            let mut id = (0..LENGTH).map(|_| b'a' + rng.below(26) as u8).collect::<Vec<u8>>();
+            let mut remaining = index;
+            for digit in 0..index_digits {
+                let letter = b'a' + (remaining % 26) as u8;
+                id[index_positions[digit]] = letter;
+                id[index_positions[digit + index_digits]] = letter;
+                remaining /= 26;
+            }
+            id
+        })
+        .collect::<Vec<Vec<u8>>>();
+
+    let mut near_match = rng.choose(&ids).clone();
+    let position = *rng.choose(free_positions);
+    near_match[position] = b'a' + ((near_match[position] - b'a' + 1 + rng.below(25) as u8) % 26);
+    ids.push(near_match);
+    rng.shuffle(&mut ids);
+
+    let lines = ids.into_iter()
+        .map(|id| String::from_utf8(id).unwrap())
+        .collect::<Vec<String>>();
+    lines.join("\n")
+}
+
+/// Three to 65535 fabric claims, of which exactly one overlaps no other claim
+pub fn day03(rng: &mut Rng, scale: usize) -> String {
+    struct Claim { left: i64, top: i64, width: i64, height: i64 }
+
+    impl Claim {
+        fn overlaps(&self, other: &Claim) -> bool {
+            self.left < other.left + other.width && other.left < self.left + self.width &&
+                self.top < other.top + other.height && other.top < self.top + self.height
+        }
+
+        fn coords(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+            (self.left..self.left + self.width)
+                .flat_map(move |x| (self.top..self.top + self.height).map(move |y| (x, y)))
+        }
+    }
+
+    let count = scale.clamp(3, usize::from(u16::MAX));
+    // the real input has about 1300 claims on a 1000 inch square
+    let side = ((count as f64).sqrt() * 28.0).max(40.0) as i64;
+    let random_claim = |rng: &mut Rng| {
+        let width = rng.between(5, 29);
+        let height = rng.between(5, 29);
+        Claim { left: rng.between(0, side - width), top: rng.between(0, side - height), width, height }
+    };
+
+    let intact = random_claim(rng);
+    let mut claims = (1..count)
+        .map(|_| loop {
+            let claim = random_claim(rng);
+            if !claim.overlaps(&intact) { break claim }
+        })
+        .collect::<Vec<Claim>>();
+
+    // move any other claim which happens to overlap nothing onto the corner of
+    // another, which can't make a third claim intact
+    let mut used = Grid::new(side as usize, side as usize, 0u32).unwrap();
+    for coord in claims.iter().flat_map(Claim::coords) {
+        used[coord] += 1;
+    }
+    for i in 0..claims.len() {
+        if claims[i].coords().all(|coord| used[coord] == 1) {
+            let other = (i + 1 + rng.below(claims.len() as u64 - 1) as usize) % claims.len();
+            let (left, top) = (claims[other].left, claims[other].top);
+            claims[i] = Claim { left, top, width: 1, height: 1 };
+            used[(left, top)] += 1;
+        }
+    }
+
+    claims.insert(rng.below(count as u64) as usize, intact);
+
+    let lines = claims.iter()
+        .enumerate()
+        .map(|(i, c)| format!("#{} @ {},{}: {}x{}", i + 1, c.left, c.top, c.width, c.height))
+        .collect::<Vec<String>>();
+    lines.join("\n")
+}
+
+/// Shuffled guard records for at least one night, where every guard who starts a
+/// shift falls asleep at least once
+pub fn day04(rng: &mut Rng, scale: usize) -> String {
+    const DAYS_IN_MONTH: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+    let nights = scale.max(1);
+    let guard_count = ((nights as f64).sqrt() as usize + 1).min(3499);
+    let mut guards = HashSet::new();
+    while guards.len() < guard_count {
+        guards.insert(rng.between(1, 3499));
+    }
+    let guards = {
+        let mut guards = guards.into_iter().collect::<Vec<i64>>();
+        guards.sort();
+        guards
+    };
+
+    // leap years are ignored, which is harmless as long as the dates sort correctly
+    let date = |day: usize| {
+        let year = 1518 + day / 365;
+        let mut day = (day % 365) as u32;
+        let mut month = 0;
+        while day >= DAYS_IN_MONTH[month] {
+            day -= DAYS_IN_MONTH[month];
+            month += 1;
+        }
+        format!("{}-{:02}-{:02}", year, month + 1, day + 1)
+    };
+
+    let mut lines = vec![];
+    for night in 1..=nights {
+        let guard = rng.choose(&guards);
+        let mut minute = if rng.chance(1, 2) {
+            lines.push(format!("[{} 23:{:02}] Guard #{} begins shift", date(night - 1), rng.between(45, 59), guard));
+            0
+        } else {
+            let start = rng.between(0, 5);
+            lines.push(format!("[{} 00:{:02}] Guard #{} begins shift", date(night), start, guard));
+            start + 1
+        };
+
+        let naps = rng.between(1, 3);
+        for _ in 0..naps {
+            if minute > 57 { break }
+            let asleep = rng.between(minute, (minute + 20).min(57));
+            let awake = rng.between(asleep + 1, (asleep + 30).min(59));
+            lines.push(format!("[{} 00:{:02}] falls asleep", date(night), asleep));
+            lines.push(format!("[{} 00:{:02}] wakes up", date(night), awake));
+            minute = awake + 1;
+        }
+    }
+
+    rng.shuffle(&mut lines);
+    lines.join("\n")
+}
+
+/// A polymer of at least one unit, where half the units are the opposite polarity
+/// of the unit before them, so chains of reactions are common
+pub fn day05(rng: &mut Rng, scale: usize) -> String {
+    let mut polymer = String::with_capacity(scale.max(1));
+    let mut previous = None;
+
+    for _ in 0..scale.max(1) {
+        let unit = match previous {
+            Some(unit) if rng.chance(1, 2) => opposite(unit),
+            _ => {
+                let letter = (b'a' + rng.below(26) as u8) as char;
+                if rng.chance(1, 2) { letter.to_ascii_uppercase() } else { letter }
+            },
+        };
+        polymer.push(unit);
+        previous = Some(unit);
+    }
+
+    polymer
+}
+
+fn opposite(unit: char) -> char {
+    if unit.is_ascii_lowercase() { unit.to_ascii_uppercase() } else { unit.to_ascii_lowercase() }
+}
+
+/// At least five distinct coordinates, including a point boxed in by four others
+/// so there is always a finite area
+pub fn day06(rng: &mut Rng, scale: usize) -> String {
+    let count = scale.max(5);
+    // the real input has 50 points in a square of about 350
+    let side = ((count as f64).sqrt() * 50.0).max(10.0) as i64;
+    let (centre, offset) = (side / 2, side / 4);
+
+    let mut seen = HashSet::new();
+    let mut points = vec![
+        (centre, centre),
+        (centre - offset, centre),
+        (centre + offset, centre),
+        (centre, centre - offset),
+        (centre, centre + offset),
+    ];
+    seen.extend(points.iter().copied());
+
+    while points.len() < count {
+        let point = (rng.between(0, side - 1), rng.between(0, side - 1));
+        if seen.insert(point) { points.push(point); }
+    }
+    rng.shuffle(&mut points);
+
+    let lines = points.iter()
+        .map(|(x, y)| format!("{}, {}", x, y))
+        .collect::<Vec<String>>();
+    lines.join("\n")
+}
+
+/// Dependencies between two to 26 steps, named by capital letters, which always
+/// form a connected acyclic graph
+pub fn day07(rng: &mut Rng, scale: usize) -> String {
+    let count = scale.clamp(2, 26);
+    let mut steps = (0..count as u8).map(|i| (b'A' + i) as char).collect::<Vec<char>>();
+    rng.shuffle(&mut steps);
+
+    // each step depends on at least one step before it in the shuffled order
+    let mut lines = vec![];
+    for i in 1..count {
+        let mut prerequisites = BTreeSet::new();
+        prerequisites.insert(rng.below(i as u64) as usize);
+        for _ in 0..rng.below(4) {
+            prerequisites.insert(rng.below(i as u64) as usize);
+        }
+
+        for prerequisite in prerequisites {
+            lines.push(format!("Step {} must be finished before step {} can begin.", steps[prerequisite], steps[i]));
+        }
+    }
+
+    rng.shuffle(&mut lines);
+    lines.join("\n")
+}
+
+/// A license tree of at least one node, each with one to three metadata entries
+///
+/// Every node after the root picks a random earlier node as its parent, which keeps
+/// the tree shallow enough to parse recursively even with millions of nodes.
+pub fn day08(rng: &mut Rng, scale: usize) -> String {
+    let count = scale.max(1);
+    let mut children = vec![vec![]; count];
+    for node in 1..count {
+        let parent = rng.below(node as u64) as usize;
+        children[parent].push(node);
+    }
+    let metadata_counts = (0..count).map(|_| rng.between(1, 3)).collect::<Vec<i64>>();
+
+    let mut numbers = vec![];
+    // nodes still to be written, and whether their header has already been written
+    let mut stack = vec![(0, false)];
+    while let Some((node, header_written)) = stack.pop() {
+        if header_written {
+            numbers.extend((0..metadata_counts[node]).map(|_| rng.between(1, 9)));
+        } else {
+            numbers.push(children[node].len() as i64);
+            numbers.push(metadata_counts[node]);
+            stack.push((node, true));
+            stack.extend(children[node].iter().rev().map(|&child| (child, false)));
+        }
+    }
+
+    let numbers = numbers.iter()
+        .map(i64::to_string)
+        .collect::<Vec<String>>();
+    numbers.join(" ")
+}
+
+/// Marble game parameters where the last marble is worth at least 25 points, so a
+/// score is always made
+pub fn day09(rng: &mut Rng, scale: usize) -> String {
+    let players = rng.between(2, 500);
+    format!("{} players; last marble is worth {} points", players, scale.max(25))
+}
+
+/// One item per line, as most puzzle inputs are laid out
+#[cfg(test)]
+pub fn lines<T: std::fmt::Display>(items: &[T]) -> String {
+    items.iter().map(T::to_string).collect::<Vec<String>>().join("\n")
+}
+
+/// Checks that parsing the input generated for each seed, at the scale for that
+/// seed, and serializing it gives back the same text, which parses to the same value
+#[cfg(test)]
+pub fn assert_round_trip<S>(
+    seeds: std::ops::Range<u64>,
+    generate: fn(&mut Rng, usize) -> String,
+    scale: impl Fn(u64) -> usize,
+    serialize: impl Fn(&S::Input) -> String,
+) where
+    S: crate::solution::Solution,
+    S::Input: PartialEq + std::fmt::Debug,
+{
+    for seed in seeds {
+        let input = generate(&mut Rng::new(seed), scale(seed));
+        let parsed = S::parse(&input).unwrap_or_else(|e| panic!("seed {}: {}", seed, e));
+        let serialized = serialize(&parsed);
+
+        assert_eq!(input, serialized, "seed {}", seed);
+        assert_eq!(Ok(parsed), S::parse(&serialized), "seed {}", seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{Part, Solution, solver};
+    use crate::{day02, day03, day07};
+
+    /// Solves both parts of a generated input, failing on a parse error
+    fn solve(day: u8, seed: u64, scale: usize) -> Vec<Option<String>> {
+        let input = generate(day, seed, scale).unwrap();
+        let parsed = solver(day).unwrap().parse(&input)
+            .unwrap_or_else(|e| panic!("day {} seed {} scale {}: {}", day, seed, scale, e));
+
+        Part::ALL.iter().map(|&part| parsed.solve(part)).collect()
+    }
+
+    #[test]
+    fn rng_is_reproducible() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+
+        assert_eq!((0..10).map(|_| a.next_u64()).collect::<Vec<_>>(), (0..10).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert!((0..1000).all(|_| (-3..=3).contains(&a.between(-3, 3))));
+    }
+
+    #[test]
+    fn same_seed_same_input() {
+        for day in 1..=9 {
+            assert_eq!(generate(day, 42, 100), generate(day, 42, 100), "day {}", day);
+            assert_ne!(generate(day, 42, 100), generate(day, 43, 100), "day {}", day);
+        }
+        assert_eq!(None, generate(10, 42, 100));
+    }
+
+    #[test]
+    fn every_day_parses_and_solves() {
+        for seed in 0..3 {
+            for &scale in &[0, 1, 10, 40] {
+                for day in 1..=9 {
+                    let answers = solve(day, seed, scale);
+                    assert!(answers[0].is_some(), "day {} seed {} scale {}", day, seed, scale);
+                    if day <= 5 {
+                        assert!(answers[1].is_some(), "day {} seed {} scale {}", day, seed, scale);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn day02_exactly_one_near_match() {
+        for seed in 0..5 {
+            let input = generate(2, seed, 200).unwrap();
+            let ids = input.lines().map(String::from).collect::<Vec<String>>();
+
+            let near_matches = ids.iter().enumerate()
+                .flat_map(|(i, a)| ids[i + 1..].iter().map(move |b| (a, b)))
+                .filter(|(a, b)| a.chars().zip(b.chars()).filter(|(x, y)| x != y).count() <= 1)
+                .count();
+
+            assert_eq!(1, near_matches);
+            assert_eq!(Some(25), day02::BoxIds::new(ids.clone()).unwrap().find_near_match().map(|common| common.len()));
+        }
+    }
+
+    #[test]
+    fn day03_exactly_one_intact_claim() {
+        for seed in 0..5 {
+            let sections = day03::Day03::parse(&generate(3, seed, 300).unwrap()).unwrap();
+            let used = day03::calc_used_fabric(&sections).unwrap();
+
+            let intact = sections.iter()
+                .filter(|section| section.coords().all(|coord| used[coord] == 1))
+                .count();
+            assert_eq!(1, intact);
+        }
+    }
+
+    #[test]
+    fn day07_every_step_performed() {
+        for &scale in &[2, 10, 26, 100] {
+            let prereqs = day07::Day07::parse(&generate(7, 1, scale).unwrap()).unwrap();
+
+            assert_eq!(scale.min(26), day07::step_order(&prereqs).unwrap().len());
+        }
+    }
+
+    #[test]
+    fn day08_large_tree() {
+        assert!(solve(8, 1, 100_000)[0].is_some());
+    }
+}
@@ -0,0 +1,191 @@
+//! Points in two and three dimensions, and the metrics used to measure between them
+
+use std::fmt;
+use std::ops::{Add, Sub};
+
+/// How far apart two points are, given the absolute difference along each axis
+pub trait Metric {
+    fn distance(deltas: &[u64]) -> u64;
+}
+
+/// Sum of the differences along each axis, as when moving along a grid
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Manhattan;
+
+/// Largest difference along any axis, as when diagonal moves are allowed
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Chebyshev;
+
+/// Square of the straight line distance, which orders points the same way without
+/// needing a square root
+///
+/// Saturates at `u64::MAX` rather than overflowing for very distant points.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SquaredEuclidean;
+
+impl Metric for Manhattan {
+    fn distance(deltas: &[u64]) -> u64 {
+        deltas.iter().sum()
+    }
+}
+
+impl Metric for Chebyshev {
+    fn distance(deltas: &[u64]) -> u64 {
+        deltas.iter().copied().max().unwrap_or(0)
+    }
+}
+
+impl Metric for SquaredEuclidean {
+    fn distance(deltas: &[u64]) -> u64 {
+        deltas.iter().fold(0, |acc: u64, delta| acc.saturating_add(delta.saturating_mul(*delta)))
+    }
+}
+
+/// Absolute difference between two coordinates, which always fits in a `u64` for
+/// coordinates of up to 64 bits
+fn delta<T: Copy + Into<i128>>(a: T, b: T) -> u64 {
+    (a.into() - b.into()).unsigned_abs() as u64
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+/// Writes the coordinates separated by commas, as in the puzzle inputs
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}", self.x, self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}, {}", self.x, self.y, self.z)
+    }
+}
+
+impl<T: Copy + Into<i128>> Point<T> {
+    pub fn distance<M: Metric>(self, other: Self) -> u64 {
+        M::distance(&[delta(self.x, other.x), delta(self.y, other.y)])
+    }
+}
+
+impl<T: Copy + Into<i128>> Point3<T> {
+    pub fn distance<M: Metric>(self, other: Self) -> u64 {
+        M::distance(&[delta(self.x, other.x), delta(self.y, other.y), delta(self.z, other.z)])
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point { x: self.x + other.x, y: self.y + other.y }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point { x: self.x - other.x, y: self.y - other.y }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point3<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point3 { x: self.x + other.x, y: self.y + other.y, z: self.z + other.z }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point3<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point3 { x: self.x - other.x, y: self.y - other.y, z: self.z - other.z }
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances_2d() {
+        let a = Point::new(1i32, -2);
+        let b = Point::new(-3, 5);
+
+        assert_eq!(11, a.distance::<Manhattan>(b));
+        assert_eq!(7, a.distance::<Chebyshev>(b));
+        assert_eq!(65, a.distance::<SquaredEuclidean>(b));
+        assert_eq!(0, a.distance::<Manhattan>(a));
+    }
+
+    #[test]
+    fn distances_3d() {
+        let a = Point3::new(0i64, 0, 0);
+        let b = Point3::new(1, -2, 3);
+
+        assert_eq!(6, a.distance::<Manhattan>(b));
+        assert_eq!(3, a.distance::<Chebyshev>(b));
+        assert_eq!(14, a.distance::<SquaredEuclidean>(b));
+    }
+
+    #[test]
+    fn distance_at_extremes() {
+        let a = Point::new(i64::MIN, 0);
+        let b = Point::new(i64::MAX, 0);
+
+        assert_eq!(u64::MAX, a.distance::<Manhattan>(b));
+        assert_eq!(u64::MAX, a.distance::<SquaredEuclidean>(b));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("1, -2", Point::new(1, -2).to_string());
+        assert_eq!("0, 4, -7", Point3::new(0, 4, -7).to_string());
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(Point::new(4, -1), Point::new(1, 2) + Point::from((3, -3)));
+        assert_eq!(Point3::new(-2, 0, 1), Point3::new(1, 2, 3) - Point3::from((3, 2, 2)));
+    }
+}
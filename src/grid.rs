@@ -0,0 +1,373 @@
+//! A dense rectangular grid of cells addressed by signed `(x, y)` coordinates
+//!
+//! The top left cell is at the grid's origin, which may be negative, with `x`
+//! increasing to the right and `y` increasing downwards. Cells are stored row by row.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+pub type Coord = (i64, i64);
+
+/// Offsets to the neighbours sharing an edge with a cell
+const NEIGHBOURS_4: [Coord; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets to the neighbours sharing an edge or a corner with a cell
+const NEIGHBOURS_8: [Coord; 8] = [(-1, -1), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0)];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedGrid<T>"))]
+pub struct Grid<T> {
+    origin: Coord,
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+/// A grid as deserialized, before checking it has a cell for every coordinate
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedGrid<T> {
+    origin: Coord,
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<UncheckedGrid<T>> for Grid<T> {
+    type Error = String;
+
+    fn try_from(grid: UncheckedGrid<T>) -> Result<Self, Self::Error> {
+        let UncheckedGrid { origin, width, height, cells } = grid;
+        if width.checked_mul(height) != Some(cells.len()) {
+            return Err(format!("{} cells don't fill a {}x{} grid", cells.len(), width, height));
+        }
+
+        Ok(Grid { origin, width, height, cells })
+    }
+}
+
+/// Returned when a grid has more cells than can be counted or allocated, or than
+/// `MAX_CELLS`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SizeError {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a {}x{} grid is too large to allocate", self.width, self.height)
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+/// The most cells a grid may have, far more than any puzzle needs, so a grid spanning
+/// coordinates billions apart fails straight away instead of filling memory
+pub const MAX_CELLS: usize = 1 << 31;
+
+/// The number of cells in a `width` by `height` grid, if it isn't too many
+fn cell_count(width: usize, height: usize) -> Result<usize, SizeError> {
+    width.checked_mul(height)
+        .filter(|&len| len <= MAX_CELLS)
+        .ok_or(SizeError { width, height })
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a grid with its origin at `(0, 0)` and every cell set to `fill`
+    pub fn new(width: usize, height: usize, fill: T) -> Result<Self, SizeError> {
+        Self::with_origin((0, 0), width, height, fill)
+    }
+
+    /// Creates a grid with every cell set to `fill`, failing rather than aborting if
+    /// there are too many cells to allocate
+    pub fn with_origin(origin: Coord, width: usize, height: usize, fill: T) -> Result<Self, SizeError> {
+        let error = SizeError { width, height };
+        let len = cell_count(width, height)?;
+
+        let mut cells = Vec::new();
+        cells.try_reserve_exact(len).map_err(|_| error)?;
+        cells.resize(len, fill);
+
+        Ok(Grid { origin, width, height, cells })
+    }
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid by calling `f` with the coordinates of each cell in row order,
+    /// failing rather than aborting if there are too many cells to allocate
+    pub fn from_fn<F>(origin: Coord, width: usize, height: usize, f: F) -> Result<Self, SizeError>
+        where F: FnMut(Coord) -> T
+    {
+        let error = SizeError { width, height };
+        let len = cell_count(width, height)?;
+
+        let mut grid = Grid { origin, width, height, cells: vec![] };
+        grid.cells.try_reserve_exact(len).map_err(|_| error)?;
+        let coords = grid.coords();
+        grid.cells.extend(coords.map(f));
+        Ok(grid)
+    }
+
+    /// Creates a grid with its origin at `(0, 0)` from a list of rows, returning
+    /// `None` if the rows are not all the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+
+        let cells = rows.into_iter().flatten().collect();
+        Some(Grid { origin: (0, 0), width, height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Coordinates of the top left cell
+    pub fn origin(&self) -> Coord {
+        self.origin
+    }
+
+    fn index_of(&self, (x, y): Coord) -> Option<usize> {
+        let column = usize::try_from(x.checked_sub(self.origin.0)?).ok()?;
+        let row = usize::try_from(y.checked_sub(self.origin.1)?).ok()?;
+
+        if column < self.width && row < self.height {
+            Some(row * self.width + column)
+        } else {
+            None
+        }
+    }
+
+    fn coord_of(&self, index: usize) -> Coord {
+        let column = (index % self.width) as i64;
+        let row = (index / self.width) as i64;
+
+        (self.origin.0 + column, self.origin.1 + row)
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        self.index_of(coord).is_some()
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.index_of(coord).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        self.index_of(coord).map(move |index| &mut self.cells[index])
+    }
+
+    /// Coordinates of every cell in row order
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let (left, top) = self.origin;
+        let (width, height) = (self.width as i64, self.height as i64);
+
+        (top..top + height).flat_map(move |y| (left..left + width).map(move |x| (x, y)))
+    }
+
+    /// Every cell along with its coordinates, in row order
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.cells.iter()
+            .enumerate()
+            .map(move |(index, cell)| (self.coord_of(index), cell))
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Rows from top to bottom, each from left to right
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on a chunk size of zero, and a zero width grid has no cells
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Columns from left to right, each from top to bottom
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |column| self.cells[column..].iter().step_by(self.width))
+    }
+
+    /// Coordinates of the cells on the edge of the grid, each given once, clockwise
+    /// from the top left
+    pub fn border(&self) -> impl Iterator<Item = Coord> {
+        let (left, top) = self.origin;
+        let right = left + self.width as i64 - 1;
+        let bottom = top + self.height as i64 - 1;
+
+        let top_edge = (left..=right).map(move |x| (x, top));
+        let right_edge = (top + 1..=bottom).map(move |y| (right, y));
+        let bottom_edge = (left..right).rev().map(move |x| (x, bottom)).filter(move |_| bottom > top);
+        let left_edge = (top + 1..bottom).rev().map(move |y| (left, y)).filter(move |_| right > left);
+
+        let empty = self.width == 0 || self.height == 0;
+        top_edge.chain(right_edge).chain(bottom_edge).chain(left_edge)
+            .filter(move |_| !empty)
+    }
+
+    fn neighbours<'a>(&'a self, (x, y): Coord, offsets: &'a [Coord]) -> impl Iterator<Item = Coord> + 'a {
+        offsets.iter()
+            .map(move |(dx, dy)| (x + dx, y + dy))
+            .filter(move |&coord| self.contains(coord))
+    }
+
+    /// Coordinates of the cells in the grid sharing an edge with the given cell
+    pub fn neighbours4(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        self.neighbours(coord, &NEIGHBOURS_4)
+    }
+
+    /// Coordinates of the cells in the grid sharing an edge or a corner with the given
+    /// cell
+    pub fn neighbours8(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        self.neighbours(coord, &NEIGHBOURS_8)
+    }
+
+    /// Creates a grid of the same size and origin by applying `f` to every cell
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+        where F: FnMut(&T) -> U
+    {
+        Grid {
+            origin: self.origin,
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Folds over every cell in row order, along with its coordinates
+    pub fn fold<B, F>(&self, init: B, mut f: F) -> B
+        where F: FnMut(B, Coord, &T) -> B
+    {
+        self.iter().fold(init, |acc, (coord, cell)| f(acc, coord, cell))
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &T {
+        self.get(coord)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", coord))
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut T {
+        self.get_mut(coord)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", coord))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(origin: Coord, width: usize, height: usize) -> Grid<usize> {
+        let mut next = 0;
+        Grid::from_fn(origin, width, height, |_| { next += 1; next - 1 }).unwrap()
+    }
+
+    #[test]
+    fn too_large() {
+        assert_eq!(Err(SizeError { width: usize::MAX, height: 2 }), Grid::new(usize::MAX, 2, 0u8));
+        assert_eq!(Err(SizeError { width: usize::MAX, height: 1 }), Grid::new(usize::MAX, 1, 0u64));
+        assert_eq!(Err(SizeError { width: 1 << 16, height: (1 << 15) + 1 }), Grid::new(1 << 16, (1 << 15) + 1, 0u8));
+        assert!(Grid::from_fn((0, 0), MAX_CELLS + 1, 1, |_| 0u8).is_err());
+    }
+
+    #[test]
+    fn index_with_negative_origin() {
+        let mut grid = numbered((-1, -2), 3, 2);
+
+        assert_eq!(Some(&0), grid.get((-1, -2)));
+        assert_eq!(Some(&5), grid.get((1, -1)));
+        assert_eq!(None, grid.get((2, -1)));
+        assert_eq!(None, grid.get((-1, 0)));
+        assert_eq!(None, grid.get((i64::MIN, 0)));
+
+        grid[(0, -1)] = 10;
+        assert_eq!(10, grid[(0, -1)]);
+    }
+
+    #[test]
+    fn from_rows() {
+        let grid = Grid::from_rows(vec![vec![1, 2], vec![3, 4]]).unwrap();
+
+        assert_eq!((2, 2), (grid.width(), grid.height()));
+        assert_eq!(3, grid[(0, 1)]);
+        assert_eq!(None, Grid::from_rows(vec![vec![1, 2], vec![3]]));
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = numbered((5, 5), 3, 2);
+
+        assert_eq!(vec![&[0, 1, 2][..], &[3, 4, 5][..]], grid.rows().collect::<Vec<_>>());
+        assert_eq!(
+            vec![vec![0, 3], vec![1, 4], vec![2, 5]],
+            grid.columns().map(|column| column.copied().collect::<Vec<_>>()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(5, 5), (6, 5), (7, 5), (5, 6), (6, 6), (7, 6)],
+            grid.coords().collect::<Vec<_>>()
+        );
+        assert_eq!(((7, 6), &5), grid.iter().last().unwrap());
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::new(3, 3, ()).unwrap();
+
+        assert_eq!(vec![(1, 0), (0, 1)], grid.neighbours4((0, 0)).collect::<Vec<_>>());
+        assert_eq!(4, grid.neighbours4((1, 1)).count());
+        assert_eq!(vec![(1, 0), (1, 1), (0, 1)], grid.neighbours8((0, 0)).collect::<Vec<_>>());
+        assert_eq!(8, grid.neighbours8((1, 1)).count());
+    }
+
+    #[test]
+    fn border() {
+        let grid = Grid::with_origin((-1, -1), 3, 3, ()).unwrap();
+
+        assert_eq!(
+            vec![(-1, -1), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0)],
+            grid.border().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn border_of_thin_grids() {
+        assert_eq!(vec![(0, 0), (1, 0), (2, 0)], Grid::new(3, 1, ()).unwrap().border().collect::<Vec<_>>());
+        assert_eq!(vec![(0, 0), (0, 1), (0, 2)], Grid::new(1, 3, ()).unwrap().border().collect::<Vec<_>>());
+        assert_eq!(0, Grid::new(0, 3, ()).unwrap().border().count());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let grid = numbered((-1, 2), 2, 2);
+        let json = serde_json::to_string(&grid).unwrap();
+
+        assert_eq!(r#"{"origin":[-1,2],"width":2,"height":2,"cells":[0,1,2,3]}"#, json);
+        assert_eq!(grid, serde_json::from_str(&json).unwrap());
+        assert!(serde_json::from_str::<Grid<usize>>(r#"{"origin":[0,0],"width":2,"height":2,"cells":[0]}"#).is_err());
+    }
+
+    #[test]
+    fn map_and_fold() {
+        let grid = numbered((0, 0), 2, 2).map(|n| n * 10);
+
+        assert_eq!(Grid::from_rows(vec![vec![0, 10], vec![20, 30]]).unwrap(), grid);
+        assert_eq!(60, grid.fold(0, |acc, _, n| acc + n));
+        assert_eq!(2, grid.fold(0, |acc, (x, _), _| acc + x));
+    }
+}
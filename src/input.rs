@@ -0,0 +1,206 @@
+//! Locating and loading puzzle inputs
+//!
+//! Inputs live in `<input dir>/dayNN/`, where `input.txt` is the default input and
+//! `input-<name>.txt` is a named input, such as an example or another user's input.
+//! The input dir is taken from the `AOC_INPUT_DIR` environment variable, then the
+//! `input_dir` config setting, and otherwise defaults to `src` in this crate.
+
+use crate::config::{Config, ConfigError};
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the input stored in `input.txt`
+pub const DEFAULT: &str = "default";
+
+#[derive(Debug)]
+pub enum InputError {
+    NotFound(PathBuf),
+    Io(PathBuf, io::Error),
+    Config(ConfigError),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::NotFound(path) => write!(f, "input not found at {}", path.display()),
+            InputError::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            InputError::Config(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<ConfigError> for InputError {
+    fn from(e: ConfigError) -> Self {
+        InputError::Config(e)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Inputs {
+    dir: PathBuf,
+}
+
+impl Inputs {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Inputs { dir: dir.into() }
+    }
+
+    /// Resolves the input dir from the environment and config file
+    pub fn from_env() -> Result<Self, InputError> {
+        if let Some(dir) = env::var_os("AOC_INPUT_DIR") {
+            return Ok(Inputs::new(dir));
+        }
+
+        let dir = Config::load()?.input_dir
+            .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("src"));
+
+        Ok(Inputs::new(dir))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn day_dir(&self, day: u8) -> PathBuf {
+        self.dir.join(format!("day{:02}", day))
+    }
+
+    pub fn path(&self, day: u8, name: &str) -> PathBuf {
+        let file_name = if name == DEFAULT {
+            String::from("input.txt")
+        } else {
+            format!("input-{}.txt", name)
+        };
+
+        self.day_dir(day).join(file_name)
+    }
+
+    /// Reads and normalizes the named input for a day
+    pub fn load(&self, day: u8, name: &str) -> Result<String, InputError> {
+        let path = self.path(day, name);
+
+        match fs::read_to_string(&path) {
+            Ok(raw) => Ok(normalize(&raw)),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Err(InputError::NotFound(path)),
+            Err(e) => Err(InputError::Io(path, e)),
+        }
+    }
+
+    /// Names of every input available for a day, in sorted order
+    pub fn names(&self, day: u8) -> Result<Vec<String>, InputError> {
+        let dir = self.day_dir(day);
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(InputError::Io(dir, e)),
+        };
+
+        let mut names = vec![];
+        for entry in entries {
+            let entry = entry.map_err(|e| InputError::Io(dir.clone(), e))?;
+            let file_name = entry.file_name();
+
+            match file_name.to_str() {
+                Some("input.txt") => names.push(String::from(DEFAULT)),
+                Some(file_name) => {
+                    let name = file_name.strip_prefix("input-")
+                        .and_then(|name| name.strip_suffix(".txt"));
+                    if let Some(name) = name { names.push(String::from(name)); }
+                },
+                None => {},
+            }
+        }
+
+        names.sort();
+        Ok(names)
+    }
+}
+
+/// Strips any byte order mark, converts CRLF line endings to LF, and removes
+/// trailing whitespace from each line and blank lines from the end of the input
+pub fn normalize(raw: &str) -> String {
+    let raw = raw.trim_start_matches('\u{feff}');
+
+    let lines = raw.lines()
+        .map(str::trim_end)
+        .collect::<Vec<&str>>();
+
+    lines.join("\n").trim_end().to_string()
+}
+
+/// Loads a day's input for a test, returning from the test if the input is missing
+///
+/// Puzzle inputs are personal so they may not be present, in which case the test is
+/// skipped with a message rather than failing.
+#[cfg(test)]
+macro_rules! input_or_skip {
+    ($day:expr) => {
+        input_or_skip!($day, $crate::input::DEFAULT)
+    };
+    ($day:expr, $name:expr) => {
+        match $crate::input::Inputs::from_env().and_then(|inputs| inputs.load($day, $name)) {
+            Ok(input) => input,
+            Err($crate::input::InputError::NotFound(path)) => {
+                eprintln!("skipping test, input not found at {}", path.display());
+                return;
+            },
+            Err(e) => panic!("{}", e),
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc-2018-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn normalize_line_endings() {
+        assert_eq!("+1\n-2", normalize("\u{feff}+1  \r\n-2\r\n\r\n"));
+        assert_eq!("abc", normalize("abc\n"));
+        assert_eq!("", normalize(""));
+    }
+
+    #[test]
+    fn input_paths() {
+        let inputs = Inputs::new("/aoc");
+
+        assert_eq!(PathBuf::from("/aoc/day07/input.txt"), inputs.path(7, DEFAULT));
+        assert_eq!(PathBuf::from("/aoc/day07/input-example.txt"), inputs.path(7, "example"));
+    }
+
+    #[test]
+    fn load_and_list_inputs() {
+        let dir = temp_dir("inputs");
+        fs::create_dir(dir.join("day01")).unwrap();
+        fs::write(dir.join("day01/input.txt"), "+1\r\n+2\r\n").unwrap();
+        fs::write(dir.join("day01/input-alice.txt"), "-1\n").unwrap();
+        fs::write(dir.join("day01/notes.md"), "").unwrap();
+
+        let inputs = Inputs::new(&dir);
+
+        assert_eq!(vec!["alice", DEFAULT], inputs.names(1).unwrap());
+        assert_eq!(Vec::<String>::new(), inputs.names(2).unwrap());
+        assert_eq!("+1\n+2", inputs.load(1, DEFAULT).unwrap());
+        assert_eq!("-1", inputs.load(1, "alice").unwrap());
+
+        match inputs.load(1, "bob") {
+            Err(InputError::NotFound(path)) => assert_eq!(dir.join("day01/input-bob.txt"), path),
+            other => panic!("expected not found, got {:?}", other),
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+//! Just enough JSON output for the reports written by the command line tools
+
+use std::fmt;
+
+/// Quotes a string, escaping anything JSON doesn't allow inside a string literal
+pub fn string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Joins values which are already JSON into an array
+pub fn array<I: IntoIterator<Item = String>>(values: I) -> String {
+    format!("[{}]", values.into_iter().collect::<Vec<String>>().join(","))
+}
+
+/// A JSON object whose fields are written in the order they were added
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct Object {
+    fields: Vec<(String, String)>,
+}
+
+impl Object {
+    pub fn new() -> Self {
+        Object::default()
+    }
+
+    /// Adds a field whose value is already JSON
+    pub fn raw(mut self, key: &str, value: String) -> Self {
+        self.fields.push((String::from(key), value));
+        self
+    }
+
+    pub fn string(self, key: &str, value: &str) -> Self {
+        self.raw(key, string(value))
+    }
+
+    /// Adds a number, which must display as a valid JSON number
+    pub fn number<T: fmt::Display>(self, key: &str, value: T) -> Self {
+        self.raw(key, value.to_string())
+    }
+
+    pub fn optional_string(self, key: &str, value: Option<&str>) -> Self {
+        self.raw(key, value.map_or_else(|| String::from("null"), string))
+    }
+
+    pub fn optional_number<T: fmt::Display>(self, key: &str, value: Option<T>) -> Self {
+        self.raw(key, value.map_or_else(|| String::from("null"), |value| value.to_string()))
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = self.fields.iter()
+            .map(|(key, value)| format!("{}:{}", string(key), value))
+            .collect::<Vec<String>>();
+
+        write!(f, "{{{}}}", fields.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_strings() {
+        assert_eq!(r#""a \"b\"\\\n\u0001""#, string("a \"b\"\\\n\u{1}"));
+    }
+
+    #[test]
+    fn write_object() {
+        let object = Object::new()
+            .number("day", 3)
+            .string("input", "default")
+            .optional_string("answer", None)
+            .optional_number("rate", Some(1.5))
+            .raw("parts", array(vec![String::from("1"), String::from("2")]));
+
+        assert_eq!(
+            r#"{"day":3,"input":"default","answer":null,"rate":1.5,"parts":[1,2]}"#,
+            object.to_string()
+        );
+    }
+}
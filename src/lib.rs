@@ -1,12 +1,37 @@
-#![feature(range_contains)]
-#![allow(dead_code)]
+pub mod answers;
+pub mod backend;
+pub mod bench;
+pub mod client;
+pub mod config;
+pub mod document;
+pub mod error;
+pub mod fetch;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+#[macro_use]
+pub mod input;
+pub mod json;
+pub mod parsers;
+pub mod report;
+pub mod run;
+pub mod scanner;
+pub mod solution;
+#[cfg(test)]
+mod stub_server;
+pub mod submit;
+pub mod verify;
+pub mod watch;
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+
+pub use crate::error::ParseError;
+pub use crate::solution::{Parsed, Part, Solution, Solver, Unsolved, solver, solvers};
@@ -0,0 +1,308 @@
+//! nom parsers shared between days
+//!
+//! Numbers are parsed digit by digit with checked arithmetic, so a number too large
+//! for its type fails with an overflow error pointing at the number, rather than
+//! being reported as a missing digit or silently truncated.
+
+use nom::types::CompleteStr;
+use nom::{Context, Err, ErrorKind, IResult};
+
+/// Error code used with `ErrorKind::Custom` when a number doesn't fit in its type
+pub const OVERFLOW: u32 = 1;
+
+/// Integer types which can be parsed from decimal digits
+pub trait Integer: Copy {
+    const ZERO: Self;
+
+    /// Appends a decimal digit, moving away from zero in the direction of `negative`,
+    /// or returns `None` if the result doesn't fit
+    fn push_digit(self, digit: u8, negative: bool) -> Option<Self>;
+}
+
+/// Integer types which can hold negative numbers
+pub trait Signed: Integer {}
+
+macro_rules! integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+
+                fn push_digit(self, digit: u8, negative: bool) -> Option<Self> {
+                    let shifted = self.checked_mul(10)?;
+                    if negative {
+                        shifted.checked_sub(digit as Self)
+                    } else {
+                        shifted.checked_add(digit as Self)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Signed for i8 {}
+impl Signed for i16 {}
+impl Signed for i32 {}
+impl Signed for i64 {}
+impl Signed for i128 {}
+impl Signed for isize {}
+
+/// Number of ASCII digits at the start of the input
+pub(crate) fn digit_count(input: &str) -> usize {
+    input.bytes().take_while(u8::is_ascii_digit).count()
+}
+
+/// Builds a number from a string of ASCII digits, or returns `None` if it doesn't fit
+pub(crate) fn from_digits<T: Integer>(digits: &str, negative: bool) -> Option<T> {
+    digits.bytes().try_fold(T::ZERO, |n, digit| n.push_digit(digit - b'0', negative))
+}
+
+/// Accumulates `digits`, reporting overflow at `start` so the error points at the
+/// beginning of the number including any sign
+fn accumulate<'a, T: Integer>(
+    start: CompleteStr<'a>,
+    digits: &'a str,
+    rest: &'a str,
+    negative: bool,
+) -> IResult<CompleteStr<'a>, T> {
+    from_digits(digits, negative)
+        .map(|n| (CompleteStr(rest), n))
+        .ok_or(Err::Failure(Context::Code(start, ErrorKind::Custom(OVERFLOW))))
+}
+
+/// Parses one or more decimal digits
+pub fn unsigned<T: Integer>(input: CompleteStr) -> IResult<CompleteStr, T> {
+    let len = digit_count(&input);
+    if len == 0 {
+        return Err(Err::Error(Context::Code(input, ErrorKind::Digit)));
+    }
+
+    accumulate(input, &input.0[..len], &input.0[len..], false)
+}
+
+/// Parses decimal digits with an optional leading `+` or `-`
+pub fn signed<T: Signed>(input: CompleteStr) -> IResult<CompleteStr, T> {
+    let (negative, after_sign) = match input.as_bytes().first() {
+        Some(b'-') => (true, &input.0[1..]),
+        Some(b'+') => (false, &input.0[1..]),
+        _ => (false, input.0),
+    };
+
+    let len = digit_count(after_sign);
+    if len == 0 {
+        return Err(Err::Error(Context::Code(CompleteStr(after_sign), ErrorKind::Digit)));
+    }
+
+    accumulate(input, &after_sign[..len], &after_sign[len..], negative)
+}
+
+/// Parses exactly `width` decimal digits, such as the `05` in `1518-11-05`
+pub fn fixed<T: Integer>(input: CompleteStr, width: usize) -> IResult<CompleteStr, T> {
+    let len = digit_count(&input).min(width);
+    if len < width {
+        return Err(Err::Error(Context::Code(CompleteStr(&input.0[len..]), ErrorKind::Digit)));
+    }
+
+    accumulate(input, &input.0[..len], &input.0[len..], false)
+}
+
+/// Skips any leading whitespace, including line endings, then applies the item parser
+pub fn spaced<'a, T, F>(input: CompleteStr<'a>, item: F) -> IResult<CompleteStr<'a>, T>
+    where F: Fn(CompleteStr<'a>) -> IResult<CompleteStr<'a>, T>
+{
+    item(CompleteStr(input.trim_start()))
+}
+
+/// Parses items separated by a comma, whitespace including line endings, or both,
+/// such as `+1, -2 +3`
+///
+/// Empty input is an empty list. An item which doesn't match after a separator is
+/// an error rather than the end of the list, but trailing whitespace is left
+/// unparsed.
+pub fn list<'a, T, F>(input: CompleteStr<'a>, item: F) -> IResult<CompleteStr<'a>, Vec<T>>
+    where F: Fn(CompleteStr<'a>) -> IResult<CompleteStr<'a>, T>
+{
+    let mut items = vec![];
+    if input.is_empty() {
+        return Ok((input, items));
+    }
+
+    let (mut rest, first) = item(input)?;
+    items.push(first);
+
+    loop {
+        let spaced = rest.trim_start();
+        let next = match spaced.strip_prefix(',') {
+            Some(after_comma) => after_comma.trim_start(),
+            None if spaced.is_empty() || spaced.len() == rest.len() => break,
+            None => spaced,
+        };
+
+        let (after_item, value) = item(CompleteStr(next))?;
+        items.push(value);
+        rest = after_item;
+    }
+
+    Ok((rest, items))
+}
+
+/// Parses one item per line, allowing a trailing line ending
+///
+/// Unlike `separated_list!(eol, ..)`, a line which doesn't match the item parser is
+/// an error rather than the end of the list.
+pub fn lines<'a, T, F>(input: CompleteStr<'a>, item: F) -> IResult<CompleteStr<'a>, Vec<T>>
+    where F: Fn(CompleteStr<'a>) -> IResult<CompleteStr<'a>, T>
+{
+    let mut items = vec![];
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let (after_item, value) = item(rest)?;
+        items.push(value);
+
+        rest = match after_item.strip_prefix('\n').or_else(|| after_item.strip_prefix("\r\n")) {
+            Some(next_line) => CompleteStr(next_line),
+            None if after_item.is_empty() => after_item,
+            None => return Err(Err::Error(Context::Code(after_item, ErrorKind::CrLf))),
+        };
+    }
+
+    Ok((rest, items))
+}
+
+/// Applies the item parser exactly `count` times
+///
+/// Unlike `count!`, errors point at the item which failed rather than the first item.
+pub fn counted<'a, T, F>(input: CompleteStr<'a>, item: F, count: usize) -> IResult<CompleteStr<'a>, Vec<T>>
+    where F: Fn(CompleteStr<'a>) -> IResult<CompleteStr<'a>, T>
+{
+    let mut items = Vec::with_capacity(count);
+    let mut rest = input;
+
+    for _ in 0..count {
+        let (after_item, value) = item(rest)?;
+        items.push(value);
+        rest = after_item;
+    }
+
+    Ok((rest, items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::{named, tag};
+
+    named!(
+        a<CompleteStr, CompleteStr>,
+        tag!("a")
+    );
+
+    #[test]
+    fn parse_unsigned() {
+        assert_eq!(Ok((CompleteStr(" x"), 1234u16)), unsigned(CompleteStr("1234 x")));
+        assert_eq!(Ok((CompleteStr(""), 255u8)), unsigned(CompleteStr("255")));
+        assert_eq!(
+            Err(Err::Error(Context::Code(CompleteStr("+1"), ErrorKind::Digit))),
+            unsigned::<u32>(CompleteStr("+1"))
+        );
+    }
+
+    #[test]
+    fn parse_unsigned_overflow() {
+        assert_eq!(
+            Err(Err::Failure(Context::Code(CompleteStr("256,"), ErrorKind::Custom(OVERFLOW)))),
+            unsigned::<u8>(CompleteStr("256,"))
+        );
+    }
+
+    #[test]
+    fn parse_signed() {
+        assert_eq!(Ok((CompleteStr(""), 12i32)), signed(CompleteStr("+12")));
+        assert_eq!(Ok((CompleteStr(""), 12i32)), signed(CompleteStr("12")));
+        assert_eq!(Ok((CompleteStr("\n"), -128i8)), signed(CompleteStr("-128\n")));
+        assert_eq!(
+            Err(Err::Error(Context::Code(CompleteStr("x"), ErrorKind::Digit))),
+            signed::<i32>(CompleteStr("-x"))
+        );
+    }
+
+    #[test]
+    fn parse_signed_overflow() {
+        assert_eq!(
+            Err(Err::Failure(Context::Code(CompleteStr("+128"), ErrorKind::Custom(OVERFLOW)))),
+            signed::<i8>(CompleteStr("+128"))
+        );
+        assert_eq!(
+            Err(Err::Failure(Context::Code(CompleteStr("-129"), ErrorKind::Custom(OVERFLOW)))),
+            signed::<i8>(CompleteStr("-129"))
+        );
+    }
+
+    #[test]
+    fn parse_fixed() {
+        assert_eq!(Ok((CompleteStr("3"), 1518u16)), fixed(CompleteStr("15183"), 4));
+        assert_eq!(Ok((CompleteStr("-"), 5u8)), fixed(CompleteStr("05-"), 2));
+        assert_eq!(
+            Err(Err::Error(Context::Code(CompleteStr("-"), ErrorKind::Digit))),
+            fixed::<u8>(CompleteStr("5-"), 2)
+        );
+        assert_eq!(
+            Err(Err::Failure(Context::Code(CompleteStr("999"), ErrorKind::Custom(OVERFLOW)))),
+            fixed::<u8>(CompleteStr("999"), 3)
+        );
+    }
+
+    #[test]
+    fn parse_spaced() {
+        assert_eq!(Ok((CompleteStr(" "), 7u8)), spaced(CompleteStr(" \n 7 "), unsigned));
+    }
+
+    #[test]
+    fn parse_list() {
+        assert_eq!(
+            Ok((CompleteStr("\n"), vec![1i32, -2, 3, 4])),
+            list(CompleteStr("1, -2 +3\n ,4\n"), signed)
+        );
+        assert_eq!(Ok((CompleteStr(""), vec![])), list(CompleteStr(""), signed::<i32>));
+        assert_eq!(Ok((CompleteStr("x"), vec![1i32])), list(CompleteStr("1x"), signed));
+        assert_eq!(
+            Err(Err::Error(Context::Code(CompleteStr(""), ErrorKind::Digit))),
+            list::<i32, _>(CompleteStr("1, "), signed)
+        );
+    }
+
+    #[test]
+    fn lines_trailing_newline() {
+        assert_eq!(Ok((CompleteStr(""), vec![CompleteStr("a"), CompleteStr("a")])), lines(CompleteStr("a\r\na\n"), a));
+        assert_eq!(Ok((CompleteStr(""), vec![])), lines(CompleteStr(""), a));
+    }
+
+    #[test]
+    fn lines_malformed_line() {
+        assert_eq!(
+            Err(Err::Error(Context::Code(CompleteStr("b\na"), ErrorKind::Tag))),
+            lines(CompleteStr("a\nb\na"), a)
+        );
+    }
+
+    #[test]
+    fn lines_missing_line_ending() {
+        assert_eq!(
+            Err(Err::Error(Context::Code(CompleteStr("a"), ErrorKind::CrLf))),
+            lines(CompleteStr("aa"), a)
+        );
+    }
+
+    #[test]
+    fn counted_exact() {
+        assert_eq!(Ok((CompleteStr("a"), vec![CompleteStr("a"), CompleteStr("a")])), counted(CompleteStr("aaa"), a, 2));
+        assert_eq!(
+            Err(Err::Error(Context::Code(CompleteStr("b"), ErrorKind::Tag))),
+            counted(CompleteStr("ab"), a, 2)
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Machine readable reports of solving days against their inputs, as JSON or CSV
+//!
+//! Each row is one part of one day for one input. The fields, their names and their
+//! order are the report schema which other tools rely on, so fields should only
+//! ever be added at the end.
+
+use crate::answers::Answers;
+use crate::input::{InputError, Inputs};
+use crate::json::{self, Object};
+use crate::run::run;
+use crate::solution::{Part, Solver};
+use crate::verify::{Check, check_run};
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Names of the fields of each row, in the order they are written
+pub const FIELDS: [&str; 9] = ["day", "part", "input", "answer", "expected", "status", "error", "parse_ns", "solve_ns"];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::Json => write!(f, "json"),
+            Format::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("invalid format '{}', expected json or csv", s)),
+        }
+    }
+}
+
+/// The result of solving one part for one input, with the time taken
+///
+/// Times are `None` when the input failed to parse.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Row {
+    pub check: Check,
+    pub parse_time: Option<Duration>,
+    pub solve_time: Option<Duration>,
+}
+
+impl Row {
+    fn error(&self) -> Option<String> {
+        self.check.answer.as_ref().err()
+            .map(|e| format!("{} at line {}, column {}", e.kind, e.line, e.column))
+    }
+
+    /// Every field as text in schema order, with `None` for missing values
+    fn fields(&self) -> Vec<Option<String>> {
+        let check = &self.check;
+
+        vec![
+            Some(check.day.to_string()),
+            Some(check.part.to_string()),
+            Some(check.input.clone()),
+            check.answer.clone().unwrap_or(None),
+            check.expected.clone(),
+            Some(check.status().to_string()),
+            self.error(),
+            self.parse_time.map(|time| time.as_nanos().to_string()),
+            self.solve_time.map(|time| time.as_nanos().to_string()),
+        ]
+    }
+
+    pub fn to_json(&self) -> Object {
+        let check = &self.check;
+
+        Object::new()
+            .number("day", check.day)
+            .number("part", check.part)
+            .string("input", &check.input)
+            .optional_string("answer", check.answer.as_ref().ok().and_then(Option::as_deref))
+            .optional_string("expected", check.expected.as_deref())
+            .string("status", &check.status().to_string())
+            .optional_string("error", self.error().as_deref())
+            .optional_number("parse_ns", self.parse_time.map(|time| time.as_nanos()))
+            .optional_number("solve_ns", self.solve_time.map(|time| time.as_nanos()))
+    }
+}
+
+/// Checks both parts of a day for one input like `verify::check_input`, keeping the
+/// time each took
+pub fn report_input(solver: &dyn Solver, input_name: &str, input: &str, answers: &Answers) -> Vec<Row> {
+    let result = run(solver, input, &Part::ALL);
+    let checks = check_run(solver.day(), input_name, &result, answers);
+
+    match result {
+        Ok(run) => checks.into_iter()
+            .zip(&run.parts)
+            .map(|(check, part)| Row { check, parse_time: Some(run.parse_elapsed), solve_time: Some(part.elapsed) })
+            .collect(),
+        Err(_) => checks.into_iter()
+            .map(|check| Row { check, parse_time: None, solve_time: None })
+            .collect(),
+    }
+}
+
+/// Reports on the given days against each of their inputs, ordered by day then input
+pub fn report(solvers: &[Box<dyn Solver>], inputs: &Inputs, answers: &Answers) -> Result<Vec<Row>, InputError> {
+    let mut rows = vec![];
+
+    for solver in solvers {
+        for name in inputs.names(solver.day())? {
+            let input = inputs.load(solver.day(), &name)?;
+            rows.extend(report_input(&**solver, &name, &input, answers));
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Quotes a CSV field if it contains anything which would otherwise be ambiguous
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+/// Writes the rows as a JSON array of objects
+pub fn to_json(rows: &[Row]) -> String {
+    json::array(rows.iter().map(|row| row.to_json().to_string()))
+}
+
+/// Writes the rows as CSV with a header line, leaving missing values empty
+pub fn to_csv(rows: &[Row]) -> String {
+    let mut csv = FIELDS.join(",") + "\n";
+
+    for row in rows {
+        let fields = row.fields().iter()
+            .map(|field| field.as_deref().map_or_else(String::new, csv_field))
+            .collect::<Vec<String>>();
+
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Writes the rows in the given format, ending with a line break
+pub fn write(format: Format, rows: &[Row]) -> String {
+    match format {
+        Format::Json => to_json(rows) + "\n",
+        Format::Csv => to_csv(rows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solver;
+    use crate::verify::Status;
+
+    fn example_rows() -> Vec<Row> {
+        let answers = Answers::parse("[example]\nday01.part1 = \"3\"\nday01.part2 = \"7\"").unwrap();
+        let mut rows = report_input(&*solver(1).unwrap(), "example", "+1\n-2\n+3\n+1", &answers);
+        rows.extend(report_input(&*solver(1).unwrap(), "bad, \"quoted\"", "+1\nx", &answers));
+
+        for row in rows.iter_mut() {
+            row.parse_time = row.parse_time.map(|_| Duration::from_nanos(1500));
+            row.solve_time = row.solve_time.map(|_| Duration::from_nanos(20));
+        }
+
+        rows
+    }
+
+    #[test]
+    fn report_statuses() {
+        let rows = example_rows();
+        let statuses = rows.iter()
+            .map(|row| (row.check.input.as_str(), row.check.part, row.check.status()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ("example", Part::One, Status::Pass),
+                ("example", Part::Two, Status::Fail),
+                ("bad, \"quoted\"", Part::One, Status::Fail),
+                ("bad, \"quoted\"", Part::Two, Status::Fail),
+            ],
+            statuses
+        );
+    }
+
+    #[test]
+    fn report_times() {
+        let rows = report_input(&*solver(1).unwrap(), "example", "+1\n-2", &Answers::default());
+
+        assert!(rows.iter().all(|row| row.parse_time.is_some() && row.solve_time.is_some()));
+    }
+
+    #[test]
+    fn json_schema() {
+        let rows = example_rows();
+
+        assert_eq!(
+            r#"{"day":1,"part":2,"input":"example","answer":"2","expected":"7","status":"fail","error":null,"parse_ns":1500,"solve_ns":20}"#,
+            rows[1].to_json().to_string()
+        );
+        assert_eq!(
+            r#"{"day":1,"part":1,"input":"bad, \"quoted\"","answer":null,"expected":null,"status":"fail","error":"expected digit at line 2, column 1","parse_ns":null,"solve_ns":null}"#,
+            rows[2].to_json().to_string()
+        );
+        assert_eq!("[]", to_json(&[]));
+    }
+
+    #[test]
+    fn csv_schema() {
+        assert_eq!(
+            "day,part,input,answer,expected,status,error,parse_ns,solve_ns\n\
+             1,1,example,3,3,pass,,1500,20\n\
+             1,2,example,2,7,fail,,1500,20\n\
+             1,1,\"bad, \"\"quoted\"\"\",,,fail,\"expected digit at line 2, column 1\",,\n",
+            to_csv(&example_rows()[..3])
+        );
+    }
+
+    #[test]
+    fn parse_format() {
+        assert_eq!(Ok(Format::Csv), "csv".parse());
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
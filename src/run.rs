@@ -0,0 +1,154 @@
+use crate::error::ParseError;
+use crate::solution::{Part, Solver};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Answer to a single part along with the time taken to solve it
+#[derive(Debug, PartialEq)]
+pub struct PartRun {
+    pub part: Part,
+    pub answer: Option<String>,
+    pub elapsed: Duration,
+}
+
+/// Result of parsing an input and solving the requested parts for one day
+#[derive(Debug, PartialEq)]
+pub struct Run {
+    pub day: u8,
+    pub parse_elapsed: Duration,
+    pub parts: Vec<PartRun>,
+}
+
+/// Parses the input once, then solves each of the given parts, timing every phase
+pub fn run(solver: &dyn Solver, input: &str, parts: &[Part]) -> Result<Run, ParseError> {
+    let start = Instant::now();
+    let parsed = solver.parse(input)?;
+    let parse_elapsed = start.elapsed();
+
+    let parts = parts.iter()
+        .map(|&part| {
+            let start = Instant::now();
+            let answer = parsed.solve(part);
+
+            PartRun { part, answer, elapsed: start.elapsed() }
+        })
+        .collect();
+
+    Ok(Run { day: solver.day(), parse_elapsed, parts })
+}
+
+/// Result of solving one part of one day in `run_all`
+#[derive(Debug, PartialEq)]
+pub struct PartResult {
+    pub day: u8,
+    pub part: Part,
+    /// The run for just this part, which parses its own copy of the input
+    pub result: Result<Run, ParseError>,
+}
+
+/// Solves every part of every given day concurrently on up to `threads` threads
+///
+/// Each part is a separate job, so a slow part doesn't hold up the other part of
+/// the same day. Results are ordered by the position of their day in `days` then
+/// by part, however the jobs were scheduled.
+pub fn run_all(days: &[(&dyn Solver, &str)], threads: usize) -> Vec<PartResult> {
+    let jobs = days.iter()
+        .flat_map(|&(solver, input)| Part::ALL.iter().map(move |&part| (solver, input, part)))
+        .collect::<Vec<_>>();
+    let next_job = AtomicUsize::new(0);
+
+    let mut results = thread::scope(|scope| {
+        let workers = (0..threads.clamp(1, jobs.len().max(1)))
+            .map(|_| scope.spawn(|| {
+                let mut results = vec![];
+
+                loop {
+                    let index = next_job.fetch_add(1, Ordering::Relaxed);
+                    let (solver, input, part) = match jobs.get(index) {
+                        Some(&job) => job,
+                        None => break,
+                    };
+
+                    let result = run(solver, input, &[part]);
+                    results.push((index, PartResult { day: solver.day(), part, result }));
+                }
+
+                results
+            }))
+            .collect::<Vec<_>>();
+
+        workers.into_iter()
+            .flat_map(|worker| worker.join().expect("a solver panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solver;
+
+    #[test]
+    fn run_both_parts() {
+        let run = run(&*solver(1).unwrap(), "+1\n-2\n+3\n+1", &Part::ALL).unwrap();
+
+        assert_eq!(1, run.day);
+        assert_eq!(
+            vec![(Part::One, Some(String::from("3"))), (Part::Two, Some(String::from("2")))],
+            run.parts.into_iter().map(|part| (part.part, part.answer)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn run_single_part() {
+        let run = run(&*solver(5).unwrap(), "dabAcCaCBAcCcaDA", &[Part::One]).unwrap();
+
+        assert_eq!(1, run.parts.len());
+        assert_eq!(Some(String::from("10")), run.parts[0].answer);
+    }
+
+    #[test]
+    fn run_all_in_day_order() {
+        let solvers = (1..=9).map(|day| solver(day).unwrap()).collect::<Vec<_>>();
+        let inputs = ["+1\n-2\n+3\n+1", "abcde\nfghij\nklmno\npqrst\nfguij\naxcye\nwvxyz", "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2"];
+        let days = solvers.iter().take(3).rev()
+            .zip(inputs.iter().rev())
+            .map(|(solver, &input)| (&**solver, input))
+            .collect::<Vec<_>>();
+
+        for threads in [1, 2, 8] {
+            let results = run_all(&days, threads);
+            let answers = results.iter()
+                .map(|result| (result.day, result.part, result.result.as_ref().unwrap().parts[0].answer.clone()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                vec![
+                    (3, Part::One, Some(String::from("4"))),
+                    (3, Part::Two, Some(String::from("3"))),
+                    (2, Part::One, Some(String::from("0"))),
+                    (2, Part::Two, Some(String::from("fgij"))),
+                    (1, Part::One, Some(String::from("3"))),
+                    (1, Part::Two, Some(String::from("2"))),
+                ],
+                answers,
+                "{} threads", threads
+            );
+        }
+    }
+
+    #[test]
+    fn run_all_parse_error() {
+        let solver = solver(1).unwrap();
+        let results = run_all(&[(&*solver, "x")], 4);
+
+        assert_eq!(vec![Part::One, Part::Two], results.iter().map(|result| result.part).collect::<Vec<_>>());
+        assert!(results.iter().all(|result| result.result.is_err()));
+        assert!(run_all(&[], 4).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+//! A small cursor over `&str` for writing parsers by hand
+//!
+//! Errors use the same kinds and positions as the nom parsers in `parsers`, so both
+//! parser backends report a bad input identically.
+
+use crate::error::{ErrorKind, ParseError};
+use crate::parsers::{Integer, Signed, digit_count, from_digits};
+
+pub struct Scanner<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Scanner { input, offset: 0 }
+    }
+
+    /// Input which has not been consumed yet
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.offset..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// An error pointing at the given byte offset into the input
+    pub fn error_at(&self, offset: usize, kind: ErrorKind) -> ParseError {
+        ParseError::at(self.input, offset, kind)
+    }
+
+    /// An error pointing at the next unconsumed character
+    pub fn error(&self, kind: ErrorKind) -> ParseError {
+        self.error_at(self.offset, kind)
+    }
+
+    fn advance(&mut self, len: usize) {
+        self.offset += len;
+    }
+
+    /// Consumes `tag` if the input starts with it
+    pub fn eat(&mut self, tag: &str) -> bool {
+        let found = self.rest().starts_with(tag);
+        if found {
+            self.advance(tag.len());
+        }
+        found
+    }
+
+    /// Consumes exactly the text `tag`
+    ///
+    /// Input which ends part way through the tag is reported as running out of input,
+    /// as nom does.
+    pub fn tag(&mut self, tag: &str) -> Result<(), ParseError> {
+        if self.eat(tag) {
+            Ok(())
+        } else if tag.starts_with(self.rest()) {
+            Err(self.error(ErrorKind::Expected("end of input")))
+        } else {
+            Err(self.error(ErrorKind::Expected("literal text")))
+        }
+    }
+
+    /// Consumes any single character
+    pub fn char(&mut self) -> Result<char, ParseError> {
+        match self.rest().chars().next() {
+            Some(c) => {
+                self.advance(c.len_utf8());
+                Ok(c)
+            },
+            None => Err(self.error(ErrorKind::Expected("end of input"))),
+        }
+    }
+
+    /// Consumes characters for as long as `f` holds
+    pub fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.advance(len);
+        &rest[..len]
+    }
+
+    /// Skips any whitespace, including line endings
+    pub fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn number<T: Integer>(&mut self, start: usize, len: usize, negative: bool) -> Result<T, ParseError> {
+        let digits = &self.rest()[..len];
+        let n = from_digits(digits, negative).ok_or_else(|| self.error_at(start, ErrorKind::Overflow))?;
+        self.advance(len);
+        Ok(n)
+    }
+
+    /// Parses one or more decimal digits
+    pub fn unsigned<T: Integer>(&mut self) -> Result<T, ParseError> {
+        let len = digit_count(self.rest());
+        if len == 0 {
+            return Err(self.error(ErrorKind::Expected("digit")));
+        }
+
+        self.number(self.offset, len, false)
+    }
+
+    /// Parses decimal digits with an optional leading `+` or `-`
+    pub fn signed<T: Signed>(&mut self) -> Result<T, ParseError> {
+        let start = self.offset;
+        let negative = self.eat("-");
+        if !negative {
+            self.eat("+");
+        }
+
+        let len = digit_count(self.rest());
+        if len == 0 {
+            return Err(self.error(ErrorKind::Expected("digit")));
+        }
+
+        self.number(start, len, negative)
+    }
+
+    /// Parses exactly `width` decimal digits
+    pub fn fixed<T: Integer>(&mut self, width: usize) -> Result<T, ParseError> {
+        let len = digit_count(self.rest()).min(width);
+        if len < width {
+            return Err(self.error_at(self.offset + len, ErrorKind::Expected("digit")));
+        }
+
+        self.number(self.offset, len, false)
+    }
+
+    /// Consumes a line ending, or succeeds without consuming anything at the end of
+    /// the input
+    pub fn line_end(&mut self) -> Result<(), ParseError> {
+        if self.eat("\n") || self.eat("\r\n") || self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(ErrorKind::Expected("end of line")))
+        }
+    }
+
+    /// Applies `item` once per line, allowing a trailing line ending, like
+    /// `parsers::lines`
+    pub fn lines<T, F>(&mut self, mut item: F) -> Result<Vec<T>, ParseError>
+        where F: FnMut(&mut Self) -> Result<T, ParseError>
+    {
+        let mut items = vec![];
+
+        while !self.is_empty() {
+            items.push(item(self)?);
+            self.line_end()?;
+        }
+
+        Ok(items)
+    }
+
+    /// Consumes the separator between two list entries: a comma, whitespace or both
+    ///
+    /// Returns false without consuming anything if there is no separator, or only
+    /// trailing whitespace, so the list has ended.
+    pub fn list_separator(&mut self) -> bool {
+        let before_separator = self.offset;
+        self.skip_whitespace();
+
+        if self.eat(",") {
+            self.skip_whitespace();
+        } else if self.is_empty() || self.offset == before_separator {
+            self.offset = before_separator;
+            return false;
+        }
+
+        true
+    }
+
+    /// Applies `item` to each entry of a list separated by a comma, whitespace or
+    /// both, like `parsers::list`
+    pub fn list<T, F>(&mut self, mut item: F) -> Result<Vec<T>, ParseError>
+        where F: FnMut(&mut Self) -> Result<T, ParseError>
+    {
+        let mut items = vec![];
+        if self.is_empty() {
+            return Ok(items);
+        }
+
+        items.push(item(self)?);
+        while self.list_separator() {
+            items.push(item(self)?);
+        }
+
+        Ok(items)
+    }
+
+    /// Checks that nothing but whitespace is left, like `error::finish`
+    pub fn finish(mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(ErrorKind::TrailingInput))
+        }
+    }
+}
+
+/// Runs a hand-written parser over the whole input, requiring that only whitespace
+/// is left over
+pub fn complete<'a, T, F>(input: &'a str, parse: F) -> Result<T, ParseError>
+    where F: FnOnce(&mut Scanner<'a>) -> Result<T, ParseError>
+{
+    let mut scanner = Scanner::new(input);
+    let value = parse(&mut scanner)?;
+    scanner.finish()?;
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        let mut scanner = Scanner::new("12 -34 +5 0607");
+
+        assert_eq!(Ok(12u8), scanner.unsigned());
+        scanner.skip_whitespace();
+        assert_eq!(Ok(-34i32), scanner.signed());
+        scanner.skip_whitespace();
+        assert_eq!(Ok(5i8), scanner.signed());
+        scanner.skip_whitespace();
+        assert_eq!(Ok(6u8), scanner.fixed(2));
+        assert_eq!(Ok(7u8), scanner.fixed(2));
+        assert!(scanner.is_empty());
+    }
+
+    #[test]
+    fn number_errors() {
+        let error = Scanner::new("x1").unsigned::<u8>().unwrap_err();
+        assert_eq!((ErrorKind::Expected("digit"), 1), (error.kind, error.column));
+
+        let error = Scanner::new("-x").signed::<i8>().unwrap_err();
+        assert_eq!((ErrorKind::Expected("digit"), 2), (error.kind, error.column));
+
+        let error = Scanner::new("-129").signed::<i8>().unwrap_err();
+        assert_eq!((ErrorKind::Overflow, 1), (error.kind, error.column));
+
+        let error = Scanner::new("1x").fixed::<u8>(2).unwrap_err();
+        assert_eq!((ErrorKind::Expected("digit"), 2), (error.kind, error.column));
+    }
+
+    #[test]
+    fn tags() {
+        let mut scanner = Scanner::new("ab");
+        assert_eq!(Ok(()), scanner.tag("a"));
+        assert_eq!(Err(scanner.error(ErrorKind::Expected("literal text"))), scanner.tag("c"));
+        assert_eq!(Err(scanner.error(ErrorKind::Expected("end of input"))), scanner.tag("bc"));
+        assert_eq!(1, scanner.offset());
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(Ok(vec![1u8, 2, 3, 4]), complete("1, 2 3\n,4\n", |s| s.list(Scanner::unsigned)));
+        assert_eq!(Ok(vec![]), complete("", |s| s.list(Scanner::unsigned::<u8>)));
+
+        let error = complete("1,\n", |s| s.list(Scanner::unsigned::<u8>)).unwrap_err();
+        assert_eq!((ErrorKind::Expected("digit"), 2, 1), (error.kind, error.line, error.column));
+
+        let error = complete("1 2x", |s| s.list(Scanner::unsigned::<u8>)).unwrap_err();
+        assert_eq!((ErrorKind::TrailingInput, 4), (error.kind, error.column));
+    }
+
+    #[test]
+    fn lines_and_trailing_input() {
+        assert_eq!(Ok(vec![1u8, 2]), complete("1\r\n2\n", |s| s.lines(Scanner::unsigned)));
+
+        let error = complete("1\n2 x", |s| s.lines(Scanner::unsigned::<u8>)).unwrap_err();
+        assert_eq!((ErrorKind::Expected("end of line"), 2, 2), (error.kind, error.line, error.column));
+
+        let error = complete("1 2", Scanner::unsigned::<u8>).unwrap_err();
+        assert_eq!((ErrorKind::TrailingInput, 3), (error.kind, error.column));
+    }
+}
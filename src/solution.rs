@@ -0,0 +1,175 @@
+use crate::error::ParseError;
+use crate::{day01, day02, day03, day04, day05, day06, day07, day08, day09};
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// Each day's puzzle is split into two parts which share the same input
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(format!("invalid part '{}', expected 1 or 2", s)),
+        }
+    }
+}
+
+/// Answer type for parts which have not been solved yet
+///
+/// It has no values, so a `Solution` using it can only ever return `None`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unsolved {}
+
+impl fmt::Display for Unsolved {
+    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {}
+    }
+}
+
+/// A solution to one day of Advent of Code
+///
+/// Parsing is kept separate from solving so the parsed input can be shared by both
+/// parts, and so each phase can be timed on its own.
+pub trait Solution {
+    const DAY: u8;
+
+    type Input: Send + 'static;
+    type Part1: fmt::Display;
+    type Part2: fmt::Display;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+
+    /// Returns None if the input has no answer
+    fn part1(input: &Self::Input) -> Option<Self::Part1>;
+
+    /// Returns None if the input has no answer, or part two hasn't been solved
+    fn part2(input: &Self::Input) -> Option<Self::Part2>;
+}
+
+/// Type erased `Solution`, allowing every day to be stored in the registry
+pub trait Solver: Send + Sync {
+    fn day(&self) -> u8;
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Parsed>, ParseError>;
+}
+
+/// Parsed input for a day, ready to be solved
+pub trait Parsed: Send {
+    fn solve(&self, part: Part) -> Option<String>;
+}
+
+struct Registered<S>(PhantomData<fn() -> S>);
+
+struct ParsedInput<S: Solution>(S::Input);
+
+impl<S: Solution + 'static> Solver for Registered<S> {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Parsed>, ParseError> {
+        let input = S::parse(input)?;
+        Ok(Box::new(ParsedInput::<S>(input)))
+    }
+}
+
+impl<S: Solution> Parsed for ParsedInput<S> {
+    fn solve(&self, part: Part) -> Option<String> {
+        match part {
+            Part::One => S::part1(&self.0).map(|answer| answer.to_string()),
+            Part::Two => S::part2(&self.0).map(|answer| answer.to_string()),
+        }
+    }
+}
+
+fn register<S: Solution + 'static>() -> Box<dyn Solver> {
+    Box::new(Registered::<S>(PhantomData))
+}
+
+/// All solved days, ordered by day number
+pub fn solvers() -> Vec<Box<dyn Solver>> {
+    vec![
+        register::<day01::Day01>(),
+        register::<day02::Day02>(),
+        register::<day03::Day03>(),
+        register::<day04::Day04>(),
+        register::<day05::Day05>(),
+        register::<day06::Day06>(),
+        register::<day07::Day07>(),
+        register::<day08::Day08>(),
+        register::<day09::Day09>(),
+    ]
+}
+
+pub fn solver(day: u8) -> Option<Box<dyn Solver>> {
+    solvers().into_iter().find(|solver| solver.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_ordered_by_day() {
+        let days = solvers().iter().map(|solver| solver.day()).collect::<Vec<u8>>();
+
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 9], days);
+    }
+
+    #[test]
+    fn solver_by_day() {
+        assert_eq!(Some(4), solver(4).map(|solver| solver.day()));
+        assert!(solver(25).is_none());
+    }
+
+    #[test]
+    fn solve_through_registry() {
+        let parsed = solver(1).unwrap().parse("+1\n-2\n+3\n+1").unwrap();
+
+        assert_eq!(Some(String::from("3")), parsed.solve(Part::One));
+        assert_eq!(Some(String::from("2")), parsed.solve(Part::Two));
+    }
+
+    #[test]
+    fn unsolved_part() {
+        let parsed = solver(6).unwrap().parse("1, 1\n1, 6\n8, 3\n3, 4\n5, 5\n8, 9").unwrap();
+
+        assert_eq!(Some(String::from("17")), parsed.solve(Part::One));
+        assert_eq!(None, parsed.solve(Part::Two));
+    }
+
+    #[test]
+    fn part_from_str() {
+        assert_eq!(Ok(Part::Two), "2".parse());
+        assert!("3".parse::<Part>().is_err());
+    }
+}
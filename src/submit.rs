@@ -0,0 +1,412 @@
+//! Submits answers, remembering what the server said about each of them
+//!
+//! Accepted answers are recorded in the answers file under the default input. Wrong
+//! answers, and whether they were too high or too low, are recorded in a guesses
+//! file so they are never submitted again. The guesses file also remembers how
+//! long the server asked us to wait before the next submission.
+
+use crate::answers::{Answers, AnswersError};
+use crate::client::{Client, ClientError};
+use crate::config::Config;
+use crate::document::Document;
+use crate::error::ParseError;
+use crate::input::DEFAULT;
+use crate::solution::Part;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// What the server made of a submitted answer
+#[derive(Debug, PartialEq, Clone)]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without saying whether it was too high or too low
+    Incorrect,
+    /// An answer was submitted too recently, so this one was not checked
+    Wait(Duration),
+    /// The part has already been solved, or isn't unlocked yet
+    AlreadySolved,
+    /// The response didn't match any known message, so it is kept as text
+    Unrecognized(String),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "correct"),
+            Outcome::TooHigh => write!(f, "too high"),
+            Outcome::TooLow => write!(f, "too low"),
+            Outcome::Incorrect => write!(f, "incorrect"),
+            Outcome::Wait(duration) => write!(f, "submitted too recently, wait {}s", duration.as_secs()),
+            Outcome::AlreadySolved => write!(f, "already solved"),
+            Outcome::Unrecognized(text) => write!(f, "unrecognized response: {}", text),
+        }
+    }
+}
+
+/// Text of the `<article>` element with tags removed and whitespace collapsed
+fn article_text(html: &str) -> String {
+    let article = match (html.find("<article"), html.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &html[start..end],
+        _ => html,
+    };
+
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {},
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Parses durations such as `45s`, `5m` or `1m 30s`, returning `None` if the
+/// seconds overflow
+fn parse_duration(s: &str) -> Option<Duration> {
+    s.split_whitespace()
+        .try_fold(0u64, |total, part| {
+            let unit = match part.chars().last()? {
+                'h' => 3600,
+                'm' => 60,
+                's' => 1,
+                _ => return None,
+            };
+            let value = part[..part.len() - 1].parse::<u64>().ok()?;
+
+            total.checked_add(value.checked_mul(unit)?)
+        })
+        .map(Duration::from_secs)
+}
+
+/// How long the server asked us to wait before trying again after a wrong answer
+fn wrong_answer_wait(text: &str) -> Option<Duration> {
+    let after = text.split("Please wait ").nth(1)?;
+    let mut words = after.split_whitespace();
+    let amount = match words.next()? {
+        "one" => 1,
+        n => n.parse::<u64>().ok()?,
+    };
+
+    match words.next()? {
+        "minute" | "minutes" => amount.checked_mul(60).map(Duration::from_secs),
+        _ => None,
+    }
+}
+
+pub fn parse_response(html: &str) -> Outcome {
+    let text = article_text(html);
+
+    if text.contains("That's the right answer") {
+        Outcome::Correct
+    } else if text.contains("your answer is too high") {
+        Outcome::TooHigh
+    } else if text.contains("your answer is too low") {
+        Outcome::TooLow
+    } else if text.contains("That's not the right answer") {
+        Outcome::Incorrect
+    } else if text.contains("You gave an answer too recently") {
+        let wait = text.split("You have ").nth(1)
+            .and_then(|rest| rest.split(" left to wait").next())
+            .and_then(parse_duration)
+            .unwrap_or_else(|| Duration::from_secs(60));
+        Outcome::Wait(wait)
+    } else if text.contains("You don't seem to be solving the right level") {
+        Outcome::AlreadySolved
+    } else {
+        Outcome::Unrecognized(text)
+    }
+}
+
+/// Wrong answers, stored in a table per day and part
+///
+/// ```toml
+/// wait_until = "1544158800"
+///
+/// [day01.part2]
+/// 12345 = "high"
+/// ```
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct Guesses {
+    document: Document,
+}
+
+fn table(day: u8, part: Part) -> String {
+    format!("day{:02}.part{}", day, part)
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl Guesses {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Guesses { document: Document::parse(input)? })
+    }
+
+    /// Path of the guesses file, from the `guesses` config setting or `guesses.toml`
+    /// in this crate
+    pub fn default_path() -> Result<PathBuf, AnswersError> {
+        let path = Config::load()?.guesses
+            .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("guesses.toml"));
+
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, AnswersError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents)
+                .map_err(|e| AnswersError::Parse(path.to_path_buf(), e)),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Guesses::default()),
+            Err(e) => Err(AnswersError::Io(path.to_path_buf(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AnswersError> {
+        fs::write(path, self.document.to_string().trim_start())
+            .map_err(|e| AnswersError::Io(path.to_path_buf(), e))
+    }
+
+    /// Returns the outcome already known for an answer
+    ///
+    /// Numeric answers are also compared against earlier guesses, so anything above
+    /// a guess which was too high is known to be too high as well.
+    pub fn known(&self, day: u8, part: Part, answer: &str) -> Option<Outcome> {
+        let guesses = self.document.table(&table(day, part))?;
+
+        match guesses.get(answer).map(String::as_str) {
+            Some("high") => return Some(Outcome::TooHigh),
+            Some("low") => return Some(Outcome::TooLow),
+            Some(_) => return Some(Outcome::Incorrect),
+            None => {},
+        }
+
+        let answer = answer.parse::<i64>().ok()?;
+        guesses.iter()
+            .filter_map(|(guess, verdict)| guess.parse::<i64>().ok().map(|guess| (guess, verdict.as_str())))
+            .find_map(|(guess, verdict)| match verdict {
+                "high" if answer >= guess => Some(Outcome::TooHigh),
+                "low" if answer <= guess => Some(Outcome::TooLow),
+                _ => None,
+            })
+    }
+
+    /// Time left before the server will accept another answer
+    pub fn wait_remaining(&self, now: SystemTime) -> Option<Duration> {
+        let wait_until = self.document.get("", "wait_until")?.parse::<u64>().ok()?;
+
+        wait_until.checked_sub(unix_seconds(now))
+            .filter(|&remaining| remaining > 0)
+            .map(Duration::from_secs)
+    }
+
+    fn wait(&mut self, now: SystemTime, duration: Duration) {
+        let wait_until = unix_seconds(now) + duration.as_secs();
+        self.document.set("", "wait_until", &wait_until.to_string());
+    }
+
+    /// Records a wrong answer, ignoring any other outcome
+    pub fn record(&mut self, day: u8, part: Part, answer: &str, outcome: &Outcome) {
+        let verdict = match outcome {
+            Outcome::TooHigh => "high",
+            Outcome::TooLow => "low",
+            Outcome::Incorrect => "wrong",
+            _ => return,
+        };
+
+        self.document.set(&table(day, part), answer, verdict);
+    }
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    Client(ClientError),
+    /// The answer was already submitted, or is outside the bounds of earlier guesses
+    KnownBad(Outcome),
+    /// A correct answer has already been recorded for this part
+    AlreadyAnswered(String),
+    RateLimited(Duration),
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubmitError::Client(e) => write!(f, "{}", e),
+            SubmitError::KnownBad(outcome) => write!(f, "not submitted, answer is known to be {}", outcome),
+            SubmitError::AlreadyAnswered(answer) => write!(f, "not submitted, already answered with {}", answer),
+            SubmitError::RateLimited(wait) => write!(f, "not submitted, wait {}s before submitting again", wait.as_secs()),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+impl From<ClientError> for SubmitError {
+    fn from(e: ClientError) -> Self {
+        SubmitError::Client(e)
+    }
+}
+
+/// Submits an answer unless it is already known to be right or wrong, then records
+/// the outcome
+///
+/// The caller is responsible for saving the answers and guesses afterwards.
+pub fn submit(
+    client: &Client,
+    answers: &mut Answers,
+    guesses: &mut Guesses,
+    day: u8,
+    part: Part,
+    answer: &str,
+    now: SystemTime,
+) -> Result<Outcome, SubmitError> {
+    if let Some(correct) = answers.get(DEFAULT, day, part) {
+        return Err(SubmitError::AlreadyAnswered(String::from(correct)));
+    }
+    if let Some(outcome) = guesses.known(day, part, answer) {
+        return Err(SubmitError::KnownBad(outcome));
+    }
+    if let Some(wait) = guesses.wait_remaining(now) {
+        return Err(SubmitError::RateLimited(wait));
+    }
+
+    let html = client.answer(day, part, answer)?;
+    let outcome = parse_response(&html);
+
+    match &outcome {
+        Outcome::Correct => answers.set(DEFAULT, day, part, answer),
+        Outcome::Wait(wait) => guesses.wait(now, *wait),
+        _ => {
+            guesses.record(day, part, answer, &outcome);
+            if let Some(wait) = wrong_answer_wait(&article_text(&html)) {
+                guesses.wait(now, wait);
+            }
+        },
+    }
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stub_server::StubServer;
+
+    const CORRECT: &str = "<main>\n<article><p>That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer to fixing the time stream. <a href=\"/2018/day/1#part2\">[Continue to Part Two]</a></p></article>\n</main>";
+    const TOO_HIGH: &str = "<main>\n<article><p>That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data. Please wait one minute before trying again. <a href=\"/2018/day/1\">[Return to Day 1]</a></p></article>\n</main>";
+    const TOO_LOW: &str = "<article><p>That's not the right answer; your answer is too low.  Please wait 5 minutes before trying again.</p></article>";
+    const INCORRECT: &str = "<article><p>That's not the right answer.  If you're stuck, make sure you're using the full input data.</p></article>";
+    const WAIT: &str = "<article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 23s left to wait. <a href=\"/2018/day/1\">[Return to Day 1]</a></p></article>";
+    const ALREADY_SOLVED: &str = "<article><p>You don't seem to be solving the right level.  Did you already complete it? <a href=\"/2018/day/1\">[Return to Day 1]</a></p></article>";
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn parse_responses() {
+        assert_eq!(Outcome::Correct, parse_response(CORRECT));
+        assert_eq!(Outcome::TooHigh, parse_response(TOO_HIGH));
+        assert_eq!(Outcome::TooLow, parse_response(TOO_LOW));
+        assert_eq!(Outcome::Incorrect, parse_response(INCORRECT));
+        assert_eq!(Outcome::Wait(Duration::from_secs(83)), parse_response(WAIT));
+        assert_eq!(Outcome::AlreadySolved, parse_response(ALREADY_SOLVED));
+        assert_eq!(Outcome::Unrecognized(String::from("Something else")), parse_response("<article><p>Something\n else</p></article>"));
+    }
+
+    #[test]
+    fn wrong_answer_waits() {
+        assert_eq!(Some(Duration::from_secs(60)), wrong_answer_wait(&article_text(TOO_HIGH)));
+        assert_eq!(Some(Duration::from_secs(300)), wrong_answer_wait(&article_text(TOO_LOW)));
+        assert_eq!(None, wrong_answer_wait(&article_text(INCORRECT)));
+        assert_eq!(None, wrong_answer_wait("Please wait 18446744073709551615 minutes before trying again."));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(Some(Duration::from_secs(3723)), parse_duration("1h 2m 3s"));
+        assert_eq!(None, parse_duration("5 minutes"));
+        assert_eq!(None, parse_duration("18446744073709551615h"));
+        assert_eq!(None, parse_duration("18446744073709551615s 1s"));
+    }
+
+    #[test]
+    fn known_guesses() {
+        let guesses = Guesses::parse("[day01.part2]\n100 = \"high\"\n10 = \"low\"\n50 = \"wrong\"").unwrap();
+
+        assert_eq!(Some(Outcome::TooHigh), guesses.known(1, Part::Two, "100"));
+        assert_eq!(Some(Outcome::TooHigh), guesses.known(1, Part::Two, "150"));
+        assert_eq!(Some(Outcome::TooLow), guesses.known(1, Part::Two, "-3"));
+        assert_eq!(Some(Outcome::Incorrect), guesses.known(1, Part::Two, "50"));
+        assert_eq!(None, guesses.known(1, Part::Two, "51"));
+        assert_eq!(None, guesses.known(1, Part::One, "100"));
+    }
+
+    #[test]
+    fn submit_records_outcomes() {
+        let server = StubServer::start(vec![
+            (200, String::from(TOO_HIGH)),
+            (200, String::from(CORRECT)),
+        ]);
+        let client = Client::new(&server.url(), "abc123");
+        let mut answers = Answers::default();
+        let mut guesses = Guesses::default();
+
+        assert_eq!(Ok(Outcome::TooHigh), submit(&client, &mut answers, &mut guesses, 1, Part::Two, "80000", at(1000)).map_err(|e| e.to_string()));
+        assert_eq!(Some(Outcome::TooHigh), guesses.known(1, Part::Two, "80000"));
+
+        match submit(&client, &mut answers, &mut guesses, 1, Part::Two, "90000", at(1001)) {
+            Err(SubmitError::KnownBad(Outcome::TooHigh)) => {},
+            other => panic!("expected known bad answer, got {:?}", other),
+        }
+        match submit(&client, &mut answers, &mut guesses, 1, Part::Two, "77271", at(1030)) {
+            Err(SubmitError::RateLimited(wait)) => assert_eq!(Duration::from_secs(30), wait),
+            other => panic!("expected rate limit, got {:?}", other),
+        }
+
+        assert_eq!(Ok(Outcome::Correct), submit(&client, &mut answers, &mut guesses, 1, Part::Two, "77271", at(1060)).map_err(|e| e.to_string()));
+        assert_eq!(Some("77271"), answers.get(DEFAULT, 1, Part::Two));
+
+        match submit(&client, &mut answers, &mut guesses, 1, Part::Two, "77271", at(2000)) {
+            Err(SubmitError::AlreadyAnswered(answer)) => assert_eq!("77271", answer),
+            other => panic!("expected already answered, got {:?}", other),
+        }
+
+        assert_eq!(2, server.requests().len());
+    }
+
+    #[test]
+    fn submit_too_recently() {
+        let server = StubServer::start(vec![(200, String::from(WAIT))]);
+        let client = Client::new(&server.url(), "abc123");
+        let mut answers = Answers::default();
+        let mut guesses = Guesses::default();
+
+        assert_eq!(Ok(Outcome::Wait(Duration::from_secs(83))), submit(&client, &mut answers, &mut guesses, 2, Part::One, "7688", at(500)).map_err(|e| e.to_string()));
+        assert_eq!(Some(Duration::from_secs(80)), guesses.wait_remaining(at(503)));
+        assert_eq!(None, guesses.known(2, Part::One, "7688"));
+    }
+
+    #[test]
+    fn guesses_round_trip() {
+        let mut guesses = Guesses::default();
+        guesses.record(4, Part::One, "1234", &Outcome::TooLow);
+        guesses.wait(at(10), Duration::from_secs(60));
+
+        let path = std::env::temp_dir().join(format!("aoc-2018-guesses-{}.toml", std::process::id()));
+        guesses.save(&path).unwrap();
+
+        assert_eq!(guesses, Guesses::load(&path).unwrap());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,155 @@
+//! Checks every solved day against every available input with a known answer
+
+use crate::answers::Answers;
+use crate::error::ParseError;
+use crate::input::{InputError, Inputs};
+use crate::run::{Run, run};
+use crate::solution::{Part, Solver, solvers};
+
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Status {
+    Pass,
+    Fail,
+    /// There is no known answer to compare against
+    Unknown,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self {
+            Status::Pass => "pass",
+            Status::Fail => "fail",
+            Status::Unknown => "unknown",
+        };
+
+        write!(f, "{}", status)
+    }
+}
+
+/// The result of solving one part of a day for a single input
+#[derive(Debug, PartialEq, Clone)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub input: String,
+    pub answer: Result<Option<String>, ParseError>,
+    pub expected: Option<String>,
+}
+
+impl Check {
+    /// A parse error always fails, as does a day with no answer when one is expected
+    pub fn status(&self) -> Status {
+        match (&self.answer, &self.expected) {
+            (Err(_), _) => Status::Fail,
+            (Ok(answer), Some(expected)) if answer.as_ref() == Some(expected) => Status::Pass,
+            (Ok(_), Some(_)) => Status::Fail,
+            (Ok(_), None) => Status::Unknown,
+        }
+    }
+}
+
+/// Solves both parts of a day for one input, comparing with the known answers
+pub fn check_input(solver: &dyn Solver, input_name: &str, input: &str, answers: &Answers) -> Vec<Check> {
+    check_run(solver.day(), input_name, &run(solver, input, &Part::ALL), answers)
+}
+
+/// Compares the answers from running a day on one input with the known answers,
+/// failing every part if the input didn't parse
+pub fn check_run(day: u8, input_name: &str, run: &Result<Run, ParseError>, answers: &Answers) -> Vec<Check> {
+    let check = |part, answer| Check {
+        day,
+        part,
+        input: String::from(input_name),
+        answer,
+        expected: answers.get(input_name, day, part).map(String::from),
+    };
+
+    match run {
+        Ok(run) => run.parts.iter().map(|part| check(part.part, Ok(part.answer.clone()))).collect(),
+        Err(e) => Part::ALL.iter().map(|&part| check(part, Err(e.clone()))).collect(),
+    }
+}
+
+/// Checks every registered day against each of its inputs, ordered by day then input
+pub fn verify(inputs: &Inputs, answers: &Answers) -> Result<Vec<Check>, InputError> {
+    let mut checks = vec![];
+
+    for solver in solvers() {
+        for name in inputs.names(solver.day())? {
+            let input = inputs.load(solver.day(), &name)?;
+            checks.extend(check_input(&*solver, &name, &input, answers));
+        }
+    }
+
+    Ok(checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solver;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn check_statuses() {
+        let answers = Answers::parse("[example]\nday01.part1 = \"3\"\nday01.part2 = \"7\"").unwrap();
+        let checks = check_input(&*solver(1).unwrap(), "example", "+1\n-2\n+3\n+1", &answers);
+
+        assert_eq!(Status::Pass, checks[0].status());
+        assert_eq!(Status::Fail, checks[1].status());
+
+        let checks = check_input(&*solver(1).unwrap(), "other", "+1\n-2\n+3\n+1", &answers);
+
+        assert_eq!(Status::Unknown, checks[0].status());
+        assert_eq!(Status::Unknown, checks[1].status());
+    }
+
+    #[test]
+    fn check_parse_error() {
+        let checks = check_input(&*solver(1).unwrap(), "example", "+1\nx", &Answers::default());
+
+        assert!(checks[0].answer.is_err());
+        assert_eq!(Status::Fail, checks[0].status());
+    }
+
+    #[test]
+    fn unsolved_part_with_expected_answer() {
+        let answers = Answers::parse("[example]\nday08.part2 = \"66\"").unwrap();
+        let checks = check_input(&*solver(8).unwrap(), "example", include_str!("day08/input-example.txt"), &answers);
+
+        assert_eq!(Ok(None), checks[1].answer);
+        assert_eq!(Status::Fail, checks[1].status());
+    }
+
+    #[test]
+    fn verify_examples() {
+        let dir = env::temp_dir().join(format!("aoc-2018-verify-{}", process::id()));
+        for day in &["day07", "day08"] {
+            fs::create_dir_all(dir.join(day)).unwrap();
+        }
+        fs::write(dir.join("day07/input-example.txt"), include_str!("day07/input-example.txt")).unwrap();
+        fs::write(dir.join("day08/input-example.txt"), include_str!("day08/input-example.txt")).unwrap();
+
+        let answers = Answers::parse("[example]\nday07.part1 = \"CABDFE\"\nday08.part1 = \"138\"").unwrap();
+        let checks = verify(&Inputs::new(&dir), &answers).unwrap()
+            .into_iter()
+            .map(|check| (check.day, check.part, check.status()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (7, Part::One, Status::Pass),
+                (7, Part::Two, Status::Unknown),
+                (8, Part::One, Status::Pass),
+                (8, Part::Two, Status::Unknown),
+            ],
+            checks
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
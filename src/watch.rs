@@ -0,0 +1,177 @@
+//! Support for re-running a day whenever its source or inputs change
+//!
+//! Changes are found by polling file modification times, which needs no platform
+//! specific APIs. Because a change to the source needs a rebuild, each run is a
+//! `cargo run` subprocess whose output is parsed back into answers, so answers can
+//! be compared between runs.
+
+use crate::solution::Part;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Modification times of every file in a set of directories
+pub type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+/// Records the files directly inside each directory, treating a missing directory
+/// as empty
+pub fn snapshot<P: AsRef<Path>>(dirs: &[P]) -> io::Result<Snapshot> {
+    let mut files = Snapshot::new();
+
+    for dir in dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            // editors often replace files, so one may vanish between listing and reading it
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            if metadata.is_file() {
+                files.insert(entry.path(), metadata.modified()?);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Files which were added, removed or modified between two snapshots, in path order
+pub fn changed_files(before: &Snapshot, after: &Snapshot) -> Vec<PathBuf> {
+    let removed = before.keys().filter(|path| !after.contains_key(*path));
+    let added_or_modified = after.iter()
+        .filter(|(path, modified)| before.get(*path) != Some(modified))
+        .map(|(path, _)| path);
+
+    let mut changed = removed.chain(added_or_modified).cloned().collect::<Vec<PathBuf>>();
+    changed.sort();
+    changed
+}
+
+/// Answers printed by `aoc run`, by part
+pub type RunAnswers = BTreeMap<Part, String>;
+
+/// Reads the answers from the output of `aoc run`, which has a line of the form
+/// `day N part P: ANSWER (TIME)` for each part
+pub fn parse_run_output(output: &str) -> RunAnswers {
+    output.lines()
+        .filter_map(|line| {
+            let rest = line.strip_prefix("day ")?;
+            let (_day, rest) = rest.split_once(" part ")?;
+            let (part, rest) = rest.split_once(": ")?;
+            let (answer, _time) = rest.rsplit_once(" (")?;
+
+            Some((part.parse().ok()?, String::from(answer)))
+        })
+        .collect()
+}
+
+/// How the answer to one part compares with the previous run
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AnswerDiff {
+    Unchanged(Part, String),
+    Changed(Part, String, String),
+    Added(Part, String),
+    Removed(Part, String),
+}
+
+impl fmt::Display for AnswerDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnswerDiff::Unchanged(part, answer) => write!(f, "  part {}: {}", part, answer),
+            AnswerDiff::Changed(part, before, after) => write!(f, "~ part {}: {} -> {}", part, before, after),
+            AnswerDiff::Added(part, answer) => write!(f, "+ part {}: {}", part, answer),
+            AnswerDiff::Removed(part, answer) => write!(f, "- part {}: {}", part, answer),
+        }
+    }
+}
+
+/// Compares the answers of two runs, in part order
+pub fn diff_answers(before: &RunAnswers, after: &RunAnswers) -> Vec<AnswerDiff> {
+    Part::ALL.iter()
+        .filter_map(|part| match (before.get(part), after.get(part)) {
+            (Some(before), Some(after)) if before == after => Some(AnswerDiff::Unchanged(*part, after.clone())),
+            (Some(before), Some(after)) => Some(AnswerDiff::Changed(*part, before.clone(), after.clone())),
+            (None, Some(after)) => Some(AnswerDiff::Added(*part, after.clone())),
+            (Some(before), None) => Some(AnswerDiff::Removed(*part, before.clone())),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+    use std::time::Duration;
+
+    #[test]
+    fn snapshot_changes() {
+        let dir = env::temp_dir().join(format!("aoc-2018-watch-{}", process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("mod.rs"), "a").unwrap();
+        fs::write(dir.join("input.txt"), "1").unwrap();
+
+        let dirs = [dir.clone(), dir.join("missing")];
+        let before = snapshot(&dirs).unwrap();
+        assert_eq!(vec![dir.join("input.txt"), dir.join("mod.rs")], before.keys().cloned().collect::<Vec<_>>());
+
+        let file = fs::OpenOptions::new().write(true).open(dir.join("mod.rs")).unwrap();
+        file.set_modified(before[&dir.join("mod.rs")] + Duration::from_secs(1)).unwrap();
+        fs::remove_file(dir.join("input.txt")).unwrap();
+        fs::write(dir.join("input-example.txt"), "2").unwrap();
+
+        let after = snapshot(&dirs).unwrap();
+        assert_eq!(
+            vec![dir.join("input-example.txt"), dir.join("input.txt"), dir.join("mod.rs")],
+            changed_files(&before, &after)
+        );
+        assert!(changed_files(&after, &after).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_output() {
+        let output = "day 7 parsed in 20.00µs\nday 7 part 1: CABDFE (1.50ms)\nday 7 part 2: no answer (70.00ns)\n";
+        let answers = parse_run_output(output);
+
+        assert_eq!(Some(&String::from("CABDFE")), answers.get(&Part::One));
+        assert_eq!(Some(&String::from("no answer")), answers.get(&Part::Two));
+        assert!(parse_run_output("error: day 7 has not been solved").is_empty());
+    }
+
+    #[test]
+    fn diff() {
+        let before = parse_run_output("day 1 part 1: 538 (1ms)\nday 1 part 2: 77271 (1ms)");
+        let after = parse_run_output("day 1 part 1: 538 (1ms)\nday 1 part 2: 77272 (1ms)");
+
+        assert_eq!(
+            vec![
+                AnswerDiff::Unchanged(Part::One, String::from("538")),
+                AnswerDiff::Changed(Part::Two, String::from("77271"), String::from("77272")),
+            ],
+            diff_answers(&before, &after)
+        );
+        assert_eq!("~ part 2: 77271 -> 77272", diff_answers(&before, &after)[1].to_string());
+        assert_eq!(
+            vec![AnswerDiff::Added(Part::One, String::from("538")), AnswerDiff::Added(Part::Two, String::from("77271"))],
+            diff_answers(&RunAnswers::new(), &before)
+        );
+        assert_eq!(
+            vec![AnswerDiff::Removed(Part::One, String::from("538")), AnswerDiff::Removed(Part::Two, String::from("77271"))],
+            diff_answers(&before, &RunAnswers::new())
+        );
+    }
+}